@@ -1,15 +1,42 @@
-use std::borrow::Borrow;
 use std::collections::VecDeque;
-use crate::loaders::loader::load_wordlist;
-use crate::loaders::randomizer::randomizer;
+use std::time::{Duration, Instant};
+use crate::config::config::Config;
+use crate::engine::engine::{
+    Level, LiveStats, StopOnError, TestMode, TypingEngine, TypingTestState, WORD_BUFFER,
+};
+use crate::error::ErrorKind;
+use crate::export::export::{export, write, ExportFormat, EXPORT_DIR};
+use crate::goals::goals::{DailyGoal, Practice};
+use crate::history::browser::{next_mode, parse_tags, HistoryView, Playback};
+use crate::history::history::{self, Query, Store};
+use crate::history::json_store::JsonStore;
+use crate::keymap::keymap::{Action, Keymap, Screen};
+use crate::language::language::{select_language, Language, LANGUAGES_DIR};
+use crate::profile::profile::{Profile, Profiles};
+use crate::serializers::wpm_results::WpmResult;
+use crate::sound::sound::{
+    dispatch, open_sink, select_pack, SoundEvent, SoundPack, SoundSink, Sounds, SOUNDS_DIR,
+    VOLUMES,
+};
+use crate::stats::stats::Stats;
+use crate::theme::theme::{select_theme, supports_truecolor, Theme, THEMES_DIR};
+use crate::timer::timer::{TimerView, TimerViewState};
+use crate::ui::caret::CaretStyle;
+use crate::ui::memory::Memory;
+use crate::ui::mouse::MouseAreas;
+use crate::ui::stats_bar::{toggle_field, StatField};
+use crate::validation::validation::{seal, validate};
+use chrono::Local;
 use rand::seq::SliceRandom;
 use std::path::Path;
 use tui::widgets::TableState;
-use crate::serializers::wpm_results::WpmResult;
 
+/// How long an error toast stays up unless it is dismissed.
+pub const ERROR_TIMEOUT: Duration = Duration::from_secs(8);
+
+#[derive(PartialEq)]
 pub enum InputMode {
     Normal,
-    Editing,
     Typing,
 }
 
@@ -18,73 +45,405 @@ pub enum State {
     TypingTest,
     MainMenu,
     Chart,
+    Settings,
+    FocusTimer,
+    Profiles,
+    Stats,
+    History,
+}
+
+/// A change to the profiles waiting for a name or a confirmation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileEdit {
+    Create(String),
+    Rename { from: String, to: String },
+    Delete(String),
+}
+
+/// Input taken on the history screen until it is confirmed or cancelled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryEdit {
+    /// Typing the text filter, which applies as it is typed.
+    Filter,
+    /// Typing the tags of the selected result.
+    Tag(String),
+    /// Waiting for the deletion of the selected result to be confirmed.
+    Delete,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChartFilter {
+    All,
+    Recent,
+}
+
+impl ChartFilter {
+    /// How many results the `Recent` filter keeps.
+    pub const RECENT: usize = 10;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartFilter::All => "all",
+            ChartFilter::Recent => "recent",
+        }
+    }
 }
 
-pub enum TypingTestState {
-    NotStarted,
-    Running,
-    End,
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    Theme,
+    Language,
+    TestMode,
+    ExportFormat,
+    DailyGoal,
+    TextCues,
+    LargeText,
+    ReduceMotion,
+    Sounds,
+    SoundPack,
+    Volume,
+    Caret,
+    CaretBlink,
+    TapeMode,
+    FocusMode,
+    LiveStats,
+    Stat(StatField),
+    Level,
+    StopOnError,
+    NoBackspace,
+    BlindMode,
+    MemoryMode,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 26] = [
+        Setting::Theme,
+        Setting::Language,
+        Setting::TestMode,
+        Setting::ExportFormat,
+        Setting::DailyGoal,
+        Setting::TextCues,
+        Setting::LargeText,
+        Setting::ReduceMotion,
+        Setting::Sounds,
+        Setting::SoundPack,
+        Setting::Volume,
+        Setting::Caret,
+        Setting::CaretBlink,
+        Setting::TapeMode,
+        Setting::FocusMode,
+        Setting::LiveStats,
+        Setting::Stat(StatField::Left),
+        Setting::Stat(StatField::Wpm),
+        Setting::Stat(StatField::Raw),
+        Setting::Stat(StatField::Accuracy),
+        Setting::Stat(StatField::Errors),
+        Setting::Level,
+        Setting::StopOnError,
+        Setting::NoBackspace,
+        Setting::BlindMode,
+        Setting::MemoryMode,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Setting::Theme => "Theme",
+            Setting::Language => "Language",
+            Setting::TestMode => "Test mode",
+            Setting::ExportFormat => "Export format",
+            Setting::DailyGoal => "Daily goal",
+            Setting::TextCues => "Text cues",
+            Setting::LargeText => "Large text",
+            Setting::ReduceMotion => "Reduce motion",
+            Setting::Sounds => "Sounds",
+            Setting::SoundPack => "Sound pack",
+            Setting::Volume => "Volume",
+            Setting::Caret => "Caret",
+            Setting::CaretBlink => "Caret blink",
+            Setting::TapeMode => "Tape mode",
+            Setting::FocusMode => "Focus mode",
+            Setting::LiveStats => "Live stats",
+            Setting::Stat(field) => field.label(),
+            Setting::Level => "Difficulty",
+            Setting::StopOnError => "Stop on error",
+            Setting::NoBackspace => "No backspace",
+            Setting::BlindMode => "Blind mode",
+            Setting::MemoryMode => "Memory mode",
+        }
+    }
 }
 
 pub struct App<'a> {
     pub input_mode: InputMode,
     pub table_state: TableState,
-    pub text_input: String,
-    pub text_input_cursor: usize,
-    pub text_input_history: Vec<String>,
-    pub text_input_history_index: usize,
     pub items: Vec<Vec<&'a str>>,
     pub words: VecDeque<String>,
-    pub timer: Option<tokio::time::Instant>,
-    pub current_time: u64,
     pub state: State,
-    pub correct_words: usize,
-    pub incorrect_words: usize,
-    pub typing_test_state: TypingTestState,
+    pub engine: TypingEngine,
+    /// The result of the last finished test as it was saved.
+    pub last_result: Option<WpmResult>,
+    pub history: Box<dyn Store>,
+    /// Saved results oldest first, with their bars in `wpm_results`.
+    pub results: Vec<WpmResult>,
     pub wpm_results: Vec<(&'a str, u64)>,
+    /// Practice per day, for the daily goal, streak and heatmap on the menu.
+    pub practice: Practice,
+    /// Aggregates of the history, computed when the dashboard is opened.
+    pub stats: Stats,
+    pub config: Config,
+    pub themes: Vec<Theme>,
+    pub theme: Theme,
+    pub languages: Vec<Language>,
+    /// The language being typed, whose words fill `words`.
+    pub language: Language,
+    pub settings_state: TableState,
+    pub keymap: Keymap,
+    pub show_help: bool,
+    pub chart_filter: ChartFilter,
+    /// Results scrolled past on the chart with the mouse wheel.
+    pub chart_offset: usize,
+    /// Where the last draw put things that can be clicked.
+    pub mouse_areas: MouseAreas,
+    /// Countdown shown on the typing screen in time mode, following the engine's clock.
+    /// Also edited to pick a custom test duration.
+    pub test_timer: TimerView,
+    /// How the test is going, as of the last tick.
+    pub live_stats: LiveStats,
+    /// Whether `test_timer` is being edited to pick a custom test duration.
+    pub editing_timer: bool,
+    pub focus_timer: TimerView,
+    /// Set when something finished that deserves the terminal bell.
+    pub bell: bool,
+    /// When the caret last moved, a blinking caret stays shown for a while after.
+    pub caret_moved: Instant,
+    /// Which words memory mode shows.
+    pub memory: Memory,
+    pub sound_packs: Vec<SoundPack>,
+    /// Opened once sounds are turned on.
+    pub sound_sink: Option<Box<dyn SoundSink>>,
+    /// Non-fatal errors waiting to be shown, oldest first.
+    pub errors: VecDeque<ErrorKind>,
+    /// When the error at the front was first shown, it goes away after `ERROR_TIMEOUT`.
+    pub error_shown: Option<Instant>,
+    /// A one line message about something that just happened, cleared by the next key.
+    pub notice: Option<String>,
+    pub profiles: Profiles,
+    /// The profile whose settings and history are in use.
+    pub profile: Profile,
+    pub profile_names: Vec<String>,
+    pub profiles_state: TableState,
+    pub profile_edit: Option<ProfileEdit>,
+    pub history_view: HistoryView,
+    pub history_state: TableState,
+    /// Whether the selected result is open in the detail view.
+    pub history_detail: bool,
+    pub history_edit: Option<HistoryEdit>,
+    /// The selected result being typed out again in the detail view.
+    pub playback: Option<Playback>,
 }
 
-impl<'a> App<'a> {
-    pub fn new<T>(wordlist: T, result_file: T) -> Self
-    where T: AsRef<Path> {
-        let mut load_words = VecDeque::from(load_wordlist(wordlist));
-
-
-        // The data for tui-rs bar chart is required to be a tuple of an &str a u64 which is why leaking is required
-        let res = match WpmResult::from_file(&result_file) {
-            Ok(f) => {
-                f.iter()
-                    .map(|wr| (Box::leak(wr.date_time
-                        .to_string()
-                        .into_boxed_str()) as &str, wr.awpm as u64))
-                    .collect::<Vec<(&str, u64)>>()
-            },
-            Err(_) => {
-                vec![("", 0)]
+/// The parts of the app that belong to a profile.
+struct ProfileData<'a> {
+    config: Config,
+    history: Box<dyn Store>,
+    results: Vec<WpmResult>,
+    wpm_results: Vec<(&'a str, u64)>,
+    practice: Practice,
+    keymap: Keymap,
+}
+
+impl<'a> ProfileData<'a> {
+    /// Loads what it can of `profile`, queueing errors and falling back to defaults.
+    fn load(profile: &Profile, errors: &mut VecDeque<ErrorKind>) -> Self {
+        // A broken config is not fatal, the defaults are used and the error is shown
+        let config = Config::load(profile.config_file()).unwrap_or_else(|e| {
+            errors.push_back(e);
+            Config::default()
+        });
+
+        let result_file = profile.history_file();
+        // Without the configured backend the JSON history still keeps results
+        let history: Box<dyn Store> = match history::open(config.history, &result_file) {
+            Ok((history, history_errors)) => {
+                errors.extend(history_errors);
+                history
+            }
+            Err(e) => {
+                errors.push_back(e);
+                let (history, history_errors) = JsonStore::open(&result_file);
+                errors.extend(history_errors);
+                Box::new(history)
+            }
+        };
+        let results = history.query(&Query::default()).unwrap_or_else(|e| {
+            errors.push_back(e);
+            Vec::new()
+        });
+        let wpm_results = results.iter().map(chart_entry).collect::<Vec<(&str, u64)>>();
+        let practice = Practice::new(&results);
+
+        let keymap = match Keymap::from_config(&config.keys) {
+            Ok(keymap) => keymap,
+            Err(e) => {
+                errors.push_back(ErrorKind::ConfigError(profile.config_file(), Box::new(e)));
+                Keymap::default()
             }
         };
 
+        ProfileData {
+            config,
+            history,
+            results,
+            wpm_results,
+            practice,
+            keymap,
+        }
+    }
+}
+
+impl<'a> App<'a> {
+    /// Fails only when there is nothing to type, other problems are queued in `errors`.
+    /// With `choose_profile` the app opens on the profile selector.
+    pub fn new(
+        wordlist: impl AsRef<Path>,
+        profiles: Profiles,
+        profile: Profile,
+        choose_profile: bool,
+    ) -> Result<Self, ErrorKind> {
+        let default_language = Language::from_wordlist(wordlist)?;
+        let mut errors = VecDeque::new();
+
+        let ProfileData { config, history, results, wpm_results, practice, keymap } =
+            ProfileData::load(&profile, &mut errors);
+        let (themes, theme_errors) = Theme::load_all(THEMES_DIR);
+        errors.extend(theme_errors);
+        let theme = select_theme(&themes, &config.theme, supports_truecolor());
+        let (languages, language_errors) = Language::load_all(LANGUAGES_DIR, default_language);
+        errors.extend(language_errors);
+        let language = select_language(&languages, &config.language).clone();
+        let (sound_packs, sound_errors) = SoundPack::load_all(SOUNDS_DIR);
+        errors.extend(sound_errors);
+        let profile_names = profiles.list().unwrap_or_else(|e| {
+            errors.push_back(e);
+            vec![profile.name.clone()]
+        });
+
         let mut instance = Self {
             input_mode: InputMode::Normal,
             table_state: TableState::default(),
-            text_input: String::new(),
-            text_input_cursor: 0,
-            text_input_history: Vec::new(),
-            text_input_history_index: 0,
-            items: vec![vec!["Typing Test"], vec!["View Graph"]],
-            words: load_words,
-            timer: None,
-            current_time: 0,
-            state: State::MainMenu,
-            correct_words: 0,
-            incorrect_words: 0,
-            typing_test_state: TypingTestState::NotStarted,
-            wpm_results: res,
+            items: vec![
+                vec!["Typing Test"],
+                vec!["Focus Timer"],
+                vec!["View Graph"],
+                vec!["History"],
+                vec!["Statistics"],
+                vec!["Settings"],
+                vec!["Profiles"],
+            ],
+            words: VecDeque::from(language.words.clone()),
+            state: if choose_profile { State::Profiles } else { State::MainMenu },
+            engine: TypingEngine::new(config.test_mode, Vec::new()),
+            last_result: None,
+            history,
+            results,
+            wpm_results,
+            practice,
+            stats: Stats::default(),
+            config,
+            themes,
+            theme,
+            languages,
+            language,
+            settings_state: TableState::default(),
+            keymap,
+            show_help: false,
+            chart_filter: ChartFilter::All,
+            chart_offset: 0,
+            mouse_areas: MouseAreas::default(),
+            test_timer: TimerView::new(),
+            live_stats: LiveStats::default(),
+            editing_timer: false,
+            focus_timer: TimerView::new(),
+            bell: false,
+            caret_moved: Instant::now(),
+            memory: Memory::new(Instant::now()),
+            sound_packs,
+            sound_sink: None,
+            errors,
+            error_shown: None,
+            notice: None,
+            profiles,
+            profile,
+            profile_names,
+            profiles_state: TableState::default(),
+            profile_edit: None,
+            history_view: HistoryView::new(),
+            history_state: TableState::default(),
+            history_detail: false,
+            history_edit: None,
+            playback: None,
         };
 
+        instance.select_current_profile();
+        instance.open_sound();
         instance.shuffle_words();
+        instance.restart_test(true);
+
+        Ok(instance)
+    }
+
+    /// Queues a non-fatal error to be shown to the user.
+    pub fn report(&mut self, error: ErrorKind) {
+        self.errors.push_back(error);
+    }
+
+    pub fn dismiss_error(&mut self) {
+        self.errors.pop_front();
+        self.error_shown = None;
+    }
 
-        instance
+    fn save_config(&mut self) {
+        if let Err(e) = self.config.save(self.profile.config_file()) {
+            self.report(e);
+        }
+    }
+
+    /// Switches to the configured language and its words. A new test is set up unless one
+    /// is going on.
+    fn load_language(&mut self) {
+        self.language = select_language(&self.languages, &self.config.language).clone();
+        self.words = VecDeque::from(self.language.words.clone());
+        self.shuffle_words();
+        if !self.engine.is_active() {
+            self.restart_test(true);
+        }
+    }
+
+    /// Opens the audio output when sounds are on and it is not open yet. Without one only
+    /// the bell is left.
+    fn open_sound(&mut self) {
+        if self.config.sounds == Sounds::Off || self.sound_sink.is_some() {
+            return;
+        }
+        match open_sink() {
+            Ok(sink) => self.sound_sink = Some(sink),
+            Err(e) => self.report(e),
+        }
+    }
+
+    fn play_sound(&mut self, event: SoundEvent) {
+        let sink = match &mut self.sound_sink {
+            Some(sink) => sink.as_mut(),
+            None if self.config.sounds.plays(event) => {
+                self.bell |= event == SoundEvent::Error;
+                return;
+            }
+            None => return,
+        };
+        let pack = select_pack(&self.sound_packs, &self.config.sound_pack);
+        if dispatch(event, self.config.sounds, self.config.volume, pack, sink) {
+            self.bell = true;
+        }
     }
 
     pub fn shuffle_words(&mut self) {
@@ -93,31 +452,642 @@ impl<'a> App<'a> {
         self.words = VecDeque::from(another_vec);
     }
 
-    pub fn start_timer(&mut self, duration: u64) {
-        self.timer = Some(tokio::time::Instant::now());
-        tokio::spawn(async move {
-            tokio::time::sleep(tokio::time::Duration::from_secs(duration)).await;
-        });
+    /// Takes the next `count` words off the shuffled wordlist, cycling through it.
+    pub fn next_words(&mut self, count: usize) -> Vec<String> {
+        let mut words = Vec::with_capacity(count);
+        for _ in 0..count {
+            if let Some(word) = self.words.pop_front() {
+                words.push(word.clone());
+                self.words.push_back(word);
+            }
+        }
+        words
+    }
+
+    /// The keymap screen matching the current state and input mode.
+    pub fn screen(&self) -> Screen {
+        match (&self.state, &self.input_mode) {
+            (State::TypingTest, _) if self.editing_timer => Screen::Timer,
+            (State::TypingTest, InputMode::Typing) => Screen::Typing,
+            (State::TypingTest, _) => Screen::Test,
+            (State::MainMenu, _) => Screen::Menu,
+            (State::Chart, _) => Screen::Chart,
+            (State::Settings, _) => Screen::Settings,
+            (State::FocusTimer, _) => Screen::Timer,
+            (State::Profiles, _) => Screen::Profiles,
+            (State::Stats, _) => Screen::Stats,
+            (State::History, _) => Screen::History,
+        }
+    }
+
+    /// Throws away the current attempt, either retrying the same text or moving on to new words.
+    pub fn restart_test(&mut self, new_words: bool) {
+        self.last_result = None;
+        self.caret_moved = Instant::now();
+        self.memory = Memory::new(self.caret_moved);
+        self.engine.mode = self.config.test_mode;
+        self.engine.difficulty = self.config.difficulty;
+        if new_words {
+            let count = match self.engine.mode {
+                TestMode::Time(_) => WORD_BUFFER,
+                TestMode::Words(count) => count,
+            };
+            let words = self.next_words(count);
+            self.engine.reset(words);
+        } else {
+            self.engine.restart();
+        }
+    }
+
+    /// Enters typing mode, resuming a paused test or starting over after one that is over.
+    pub fn start_typing(&mut self) {
+        match self.engine.state {
+            TypingTestState::Paused => {
+                self.engine.resume_at(Instant::now());
+            }
+            TypingTestState::End | TypingTestState::Abandoned | TypingTestState::Failed => {
+                self.restart_test(true)
+            }
+            _ => {}
+        }
+        self.input_mode = InputMode::Typing;
+    }
+
+    /// Leaves typing mode, freezing the clock of a running test.
+    pub fn pause_test(&mut self) {
+        if self.engine.state == TypingTestState::Running {
+            self.engine.pause_at(Instant::now());
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Gives up on a started test so it is neither recorded nor resumable.
+    pub fn abandon_test(&mut self) {
+        if self.engine.is_active() {
+            self.engine.abandon();
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        let keystrokes = self.engine.keystrokes.len();
+        self.caret_moved = Instant::now();
+        self.engine.type_char_at(c, self.caret_moved);
+        if let Some(keystroke) = self.engine.keystrokes.get(keystrokes) {
+            // Blind mode gives no hint of a mistake, not even by sound.
+            match keystroke.correct || self.config.blind_mode {
+                true => self.play_sound(SoundEvent::Key),
+                false => self.play_sound(SoundEvent::Error),
+            }
+        }
+        self.on_tick();
+    }
+
+    pub fn backspace(&mut self) {
+        let keystrokes = self.engine.keystrokes.len();
+        self.caret_moved = Instant::now();
+        self.engine.backspace_at(self.caret_moved);
+        if self.engine.keystrokes.len() > keystrokes {
+            self.play_sound(SoundEvent::Key);
+        }
+    }
+
+    /// Takes pasted text into a profile name being typed. Tests only count what is typed,
+    /// so a paste there is turned away rather than typed out.
+    pub fn paste(&mut self, text: &str) {
+        if self.profile_edit.is_some() {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                self.profile_edit_input(Some(c));
+            }
+        } else if self.history_edit.is_some() {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                self.history_edit_input(Some(c));
+            }
+        } else if self.input_mode == InputMode::Typing {
+            self.notice = Some("Pasting is disabled during tests".to_string());
+        }
+    }
+
+    /// Advances the running test, keeping time tests supplied with words and recording
+    /// the result once it ends.
+    pub fn on_tick(&mut self) {
+        if let TestMode::Time(_) = self.engine.mode {
+            if self.engine.is_active() && self.engine.words_left() < WORD_BUFFER / 2 {
+                let words = self.next_words(WORD_BUFFER);
+                self.engine.extend(words);
+            }
+        }
+
+        if !self.errors.is_empty() {
+            let shown = *self.error_shown.get_or_insert_with(Instant::now);
+            if shown.elapsed() >= ERROR_TIMEOUT {
+                self.dismiss_error();
+            }
+        }
+
+        if self.focus_timer.update() {
+            self.bell = true;
+        }
+
+        let ended = self.engine.tick() || (self.engine.state == TypingTestState::End
+            && self.input_mode == InputMode::Typing);
+        self.live_stats = self.engine.live_stats_at(Instant::now());
+        self.memory.update(self.engine.typed.len(), Instant::now());
+        if !self.editing_timer {
+            self.sync_test_timer();
+        }
+
+        if self.engine.state == TypingTestState::Failed && self.input_mode == InputMode::Typing {
+            self.input_mode = InputMode::Normal;
+        }
+
+        if ended {
+            self.input_mode = InputMode::Normal;
+            if let Some(mut result) = self.engine.result() {
+                result.language = Some(self.config.language.clone());
+                result.layout = Some(self.config.layout.clone());
+                let keystrokes = &self.engine.keystrokes;
+                result.flags = validate(&result, keystrokes, &self.engine.typed_text());
+                seal(&mut result, keystrokes);
+                match self.history.add(&result, &self.engine.keystrokes) {
+                    Ok(()) => {
+                        self.wpm_results.push(chart_entry(&result));
+                        self.results.push(result.clone());
+                    }
+                    Err(e) => self.report(e),
+                }
+                let milestones =
+                    self.practice.record(&result, self.config.daily_goal, &self.config.milestones);
+                if !milestones.is_empty() {
+                    let text = milestones.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+                    self.notice = Some(text.join(", "));
+                    self.bell = true;
+                }
+                self.last_result = Some(result);
+            }
+        }
+    }
+
+    /// Points the test countdown at the engine's clock, which alone decides when a time test
+    /// ends.
+    fn sync_test_timer(&mut self) {
+        let duration = match self.engine.mode {
+            TestMode::Time(secs) => chrono::Duration::seconds(secs as i64),
+            TestMode::Words(_) => return,
+        };
+        let remaining = self.live_stats.time_left.unwrap_or_default();
+        let remaining = chrono::Duration::from_std(remaining).unwrap_or(duration);
+        match self.engine.state {
+            TypingTestState::NotStarted => {
+                if self.test_timer.state() != TimerViewState::Config
+                    || self.test_timer.duration() != duration
+                {
+                    self.test_timer.set_duration(duration);
+                }
+            }
+            TypingTestState::Running => self.test_timer.follow(remaining, true),
+            TypingTestState::Paused => self.test_timer.follow(remaining, false),
+            TypingTestState::End | TypingTestState::Failed | TypingTestState::Abandoned => {
+                self.test_timer.finish()
+            }
+        }
+    }
+
+    /// Starts editing the test countdown to choose a custom duration, only before a test starts.
+    pub fn edit_test_timer(&mut self) {
+        if !self.engine.is_active() {
+            self.editing_timer = true;
+            match self.config.test_mode {
+                TestMode::Time(secs) => {
+                    self.test_timer.set_duration(chrono::Duration::seconds(secs as i64))
+                }
+                TestMode::Words(_) => self.test_timer.reset(),
+            }
+        }
+    }
+
+    fn active_timer(&mut self) -> &mut TimerView {
+        if self.editing_timer {
+            &mut self.test_timer
+        } else {
+            &mut self.focus_timer
+        }
+    }
+
+    pub fn timer_digit(&mut self, c: char) {
+        if let Some(digit) = c.to_digit(10) {
+            self.active_timer().input_digit(digit as u8);
+        }
+    }
+
+    /// Handles `action` on the timer screen. Returns false for actions the timer does not use.
+    pub fn timer_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Up => self.active_timer().increment(),
+            Action::Down => self.active_timer().decrement(),
+            Action::Left => self.active_timer().focus_prev(),
+            Action::Right => self.active_timer().focus_next(),
+            Action::Select if self.editing_timer => {
+                let seconds = self.test_timer.duration().num_seconds();
+                self.editing_timer = false;
+                if seconds > 0 {
+                    self.config.test_mode = TestMode::Time(seconds as u64);
+                    self.save_config();
+                }
+                self.restart_test(true);
+            }
+            Action::Select => match self.focus_timer.state() {
+                TimerViewState::Config if self.focus_timer.duration() > chrono::Duration::zero() => {
+                    self.focus_timer.start();
+                }
+                TimerViewState::Finished => self.focus_timer.reset(),
+                _ => {}
+            },
+            Action::ToggleTimer if !self.editing_timer => self.focus_timer.pause_or_resume(),
+            Action::ResetTimer => self.active_timer().reset(),
+            Action::Back if self.editing_timer => self.editing_timer = false,
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn toggle_chart_filter(&mut self) {
+        self.chart_filter = match self.chart_filter {
+            ChartFilter::All => ChartFilter::Recent,
+            ChartFilter::Recent => ChartFilter::All,
+        };
+    }
+
+    /// Opens the dashboard with stats over the whole history.
+    pub fn open_stats(&mut self) {
+        match self.history.query(&Query::default()) {
+            Ok(results) => self.stats = Stats::new(&results),
+            Err(e) => self.report(e),
+        }
+        self.state = State::Stats;
+    }
+
+    /// Writes the results shown on the chart to `EXPORT_DIR` in the configured format.
+    pub fn export_history(&mut self) {
+        let mut results = match self.history.query(&Query::default()) {
+            Ok(results) => results,
+            Err(e) => return self.report(e),
+        };
+        if self.chart_filter == ChartFilter::Recent {
+            results.drain(..results.len().saturating_sub(ChartFilter::RECENT));
+        }
+
+        let format = self.config.export_format;
+        let path = Path::new(EXPORT_DIR).join(format!(
+            "history-{}.{}",
+            Local::now().format("%Y%m%d-%H%M%S"),
+            format.extension()
+        ));
+        match export(&results, format).and_then(|content| write(&path, &content)) {
+            Ok(()) => {
+                self.notice = Some(format!("Exported {} results to {}", results.len(), path.display()))
+            }
+            Err(e) => self.report(e),
+        }
+    }
+
+    /// Reads the profile names again and keeps the current profile selected.
+    fn refresh_profiles(&mut self) {
+        match self.profiles.list() {
+            Ok(names) => self.profile_names = names,
+            Err(e) => self.report(e),
+        }
+        self.select_current_profile();
+    }
+
+    fn select_current_profile(&mut self) {
+        let current = self.profile_names.iter().position(|n| *n == self.profile.name);
+        self.profiles_state.select(current);
+    }
+
+    fn selected_profile(&self) -> Option<String> {
+        self.profiles_state
+            .selected()
+            .and_then(|i| self.profile_names.get(i))
+            .cloned()
+    }
+
+    /// Switches to the selected profile and goes back to the menu.
+    pub fn select_profile(&mut self) {
+        if let Some(name) = self.selected_profile() {
+            self.switch_profile(&name);
+            self.state = State::MainMenu;
+        }
+    }
+
+    /// Replaces the settings and history in use with those of the profile `name`. A test in
+    /// progress belongs to the previous profile and is thrown away.
+    pub fn switch_profile(&mut self, name: &str) {
+        let profile = match self.profiles.get(name) {
+            Ok(profile) => profile,
+            Err(e) => return self.report(e),
+        };
+        if let Err(e) = self.profiles.set_last(name) {
+            self.report(e);
+        }
+        if profile == self.profile {
+            return;
+        }
+
+        self.abandon_test();
+        let ProfileData { config, history, results, wpm_results, practice, keymap } =
+            ProfileData::load(&profile, &mut self.errors);
+        self.theme = select_theme(&self.themes, &config.theme, supports_truecolor());
+        self.config = config;
+        self.history = history;
+        self.results = results;
+        self.wpm_results = wpm_results;
+        self.chart_offset = 0;
+        self.history_view = HistoryView::new();
+        self.history_state = TableState::default();
+        self.close_result();
+        self.practice = practice;
+        self.keymap = keymap;
+        self.profile = profile;
+        self.load_language();
+        self.open_sound();
+        self.select_current_profile();
+    }
+
+    /// Starts creating, renaming or deleting a profile, finished by `confirm_profile_edit`.
+    pub fn start_profile_edit(&mut self, action: Action) {
+        let selected = self.selected_profile();
+        self.profile_edit = match (action, selected) {
+            (Action::NewProfile, _) => Some(ProfileEdit::Create(String::new())),
+            (Action::RenameProfile, Some(from)) => Some(ProfileEdit::Rename {
+                to: from.clone(),
+                from,
+            }),
+            (Action::DeleteProfile, Some(name)) => Some(ProfileEdit::Delete(name)),
+            _ => None,
+        };
+    }
+
+    /// Edits the name being typed, returning false when no name is being typed.
+    pub fn profile_edit_input(&mut self, c: Option<char>) -> bool {
+        let name = match &mut self.profile_edit {
+            Some(ProfileEdit::Create(name)) | Some(ProfileEdit::Rename { to: name, .. }) => name,
+            _ => return false,
+        };
+        match c {
+            Some(c) => name.push(c),
+            None => {
+                name.pop();
+            }
+        }
+        true
+    }
+
+    pub fn confirm_profile_edit(&mut self) {
+        let edit = match self.profile_edit.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+        let res = match &edit {
+            ProfileEdit::Create(name) => self.profiles.create(name).map(|p| {
+                self.notice = Some(format!("Created profile {}", p.name));
+            }),
+            ProfileEdit::Rename { from, to } => self.profiles.rename(from, to).map(|p| {
+                self.notice = Some(format!("Renamed profile {} to {}", from, p.name));
+            }),
+            ProfileEdit::Delete(name) => self.profiles.delete(name).map(|()| {
+                self.notice = Some(format!("Deleted profile {}", name));
+            }),
+        };
+        if let Err(e) = res {
+            self.report(e);
+        }
+
+        self.refresh_profiles();
+        // The profile in use was renamed, so its history moved, or it was deleted
+        if !self.profile.dir.is_dir() {
+            let next = match edit {
+                ProfileEdit::Rename { to, .. } => self.profiles.get(&to),
+                _ => self.profiles.last(),
+            };
+            match next {
+                Ok(profile) => self.switch_profile(&profile.name),
+                Err(e) => self.report(e),
+            }
+        }
+    }
+
+    /// Opens the history table on the newest result.
+    pub fn open_history(&mut self) {
+        self.history_state.select(None);
+        self.refresh_history();
+        self.state = State::History;
+    }
+
+    /// Sorts and filters the history table again, keeping the same result selected when it
+    /// is still shown.
+    fn refresh_history(&mut self) {
+        let selected = self.selected_result();
+        self.history_view.refresh(&self.results, Local::now());
+        let rows = &self.history_view.rows;
+        let row = match selected.and_then(|i| rows.iter().position(|r| *r == i)) {
+            Some(row) => Some(row),
+            None if rows.is_empty() => None,
+            None => Some(self.history_state.selected().unwrap_or(0).min(rows.len() - 1)),
+        };
+        self.history_state.select(row);
+    }
+
+    /// Index in `results` of the result selected in the history table.
+    pub fn selected_result(&self) -> Option<usize> {
+        self.history_state
+            .selected()
+            .and_then(|row| self.history_view.rows.get(row))
+            .copied()
+    }
+
+    /// Handles `action` on the history screen, returning false for actions it does not use.
+    pub fn history_action(&mut self, action: Action) -> bool {
+        let selected = self.selected_result();
+        let view = &mut self.history_view;
+        match action {
+            Action::Select if selected.is_some() => self.history_detail = true,
+            Action::Back if self.history_detail => self.close_result(),
+            Action::SortHistory => view.column = view.column.next(),
+            Action::ReverseHistory => view.descending = !view.descending,
+            Action::FilterMode => view.filter.mode = next_mode(view.filter.mode, &self.results),
+            Action::FilterDates => view.filter.range = view.filter.range.next(),
+            Action::FilterHistory => self.history_edit = Some(HistoryEdit::Filter),
+            Action::ReplayResult => self.replay_result(),
+            Action::TagResult => {
+                if let Some(i) = selected {
+                    self.history_edit = Some(HistoryEdit::Tag(self.results[i].tags.join(", ")));
+                }
+            }
+            Action::DeleteResult if selected.is_some() => {
+                self.history_edit = Some(HistoryEdit::Delete);
+            }
+            _ => return false,
+        }
+        self.refresh_history();
+        true
+    }
+
+    pub fn close_result(&mut self) {
+        self.history_detail = false;
+        self.playback = None;
+    }
+
+    /// Opens the selected result and types it out again from its saved keystrokes.
+    fn replay_result(&mut self) {
+        let date_time = match self.selected_result() {
+            Some(i) => self.results[i].date_time,
+            None => return,
+        };
+        match self.history.replay(&date_time) {
+            Ok(keystrokes) if keystrokes.is_empty() => {
+                self.notice = Some("No keystrokes were saved for this result".to_string());
+            }
+            Ok(keystrokes) => {
+                self.playback = Some(Playback::new(keystrokes, Instant::now()));
+                self.history_detail = true;
+            }
+            Err(e) => self.report(e),
+        }
+    }
+
+    /// Edits the filter or tags being typed, returning false when neither is being typed.
+    pub fn history_edit_input(&mut self, c: Option<char>) -> bool {
+        let text = match &mut self.history_edit {
+            Some(HistoryEdit::Filter) => &mut self.history_view.filter.text,
+            Some(HistoryEdit::Tag(tags)) => tags,
+            _ => return false,
+        };
+        match c {
+            Some(c) => text.push(c),
+            None => {
+                text.pop();
+            }
+        }
+        if self.history_edit == Some(HistoryEdit::Filter) {
+            self.refresh_history();
+        }
+        true
+    }
+
+    /// Drops the input being taken. A cancelled text filter is cleared.
+    pub fn cancel_history_edit(&mut self) {
+        if self.history_edit.take() == Some(HistoryEdit::Filter) {
+            self.history_view.filter.text.clear();
+            self.refresh_history();
+        }
+    }
+
+    pub fn confirm_history_edit(&mut self) {
+        let edit = self.history_edit.take();
+        let index = match self.selected_result() {
+            Some(index) => index,
+            None => return,
+        };
+        let date_time = self.results[index].date_time;
+        let date = date_time.format("%Y-%m-%d %H:%M");
+        match edit {
+            Some(HistoryEdit::Tag(text)) => {
+                let tags = parse_tags(&text);
+                match self.history.set_tags(&date_time, &tags) {
+                    Ok(true) => {
+                        self.notice = Some(format!("Tagged the result from {}", date));
+                        self.results[index].tags = tags;
+                    }
+                    Ok(false) => self.report(ErrorKind::MissingResult(date.to_string())),
+                    Err(e) => self.report(e),
+                }
+            }
+            Some(HistoryEdit::Delete) => match self.history.delete(&date_time) {
+                Ok(true) => {
+                    self.notice = Some(format!("Deleted the result from {}", date));
+                    self.results.remove(index);
+                    self.wpm_results.remove(index);
+                    self.practice = Practice::new(&self.results);
+                    self.history_state.select(None);
+                    self.close_result();
+                }
+                Ok(false) => self.report(ErrorKind::MissingResult(date.to_string())),
+                Err(e) => self.report(e),
+            },
+            _ => {}
+        }
+        self.refresh_history();
+    }
+
+    pub fn chart_data(&self) -> &[(&'a str, u64)] {
+        &self.wpm_results[self.chart_start()..]
+    }
+
+    /// Index of the first result on the chart.
+    pub fn chart_start(&self) -> usize {
+        let len = self.wpm_results.len();
+        match self.chart_filter {
+            ChartFilter::All => self.chart_offset.min(len.saturating_sub(1)),
+            ChartFilter::Recent => len.saturating_sub(ChartFilter::RECENT),
+        }
+    }
+
+    /// Moves the chart `by` results towards the newest, when it shows them all.
+    pub fn scroll_chart(&mut self, by: isize) {
+        if self.chart_filter == ChartFilter::All {
+            let last = self.wpm_results.len().saturating_sub(1);
+            self.chart_offset = self.chart_offset.min(last).saturating_add_signed(by).min(last);
+        }
+    }
+
+    fn selected_list(&mut self) -> (&mut TableState, usize) {
+        match self.state {
+            State::Settings => (&mut self.settings_state, Setting::ALL.len()),
+            State::Profiles => (&mut self.profiles_state, self.profile_names.len()),
+            State::History => (&mut self.history_state, self.history_view.rows.len()),
+            _ => (&mut self.table_state, self.items.len()),
+        }
+    }
+
+    /// The selected row of the list on screen.
+    pub fn selected_row(&mut self) -> Option<usize> {
+        self.selected_list().0.selected()
+    }
+
+    pub fn select_row(&mut self, index: usize) {
+        let (table_state, len) = self.selected_list();
+        if index < len {
+            table_state.select(Some(index));
+        }
     }
 
     pub fn up(&mut self) {
-        let i = match self.table_state.selected() {
+        let (table_state, len) = self.selected_list();
+        if len == 0 {
+            return;
+        }
+        let i = match table_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - 1
+                    len - 1
                 } else {
                     i - 1
                 }
             }
             None => 0,
         };
-        self.table_state.select(Some(i));
+        table_state.select(Some(i));
     }
 
     pub fn down(&mut self) {
-        let i = match self.table_state.selected() {
+        let (table_state, len) = self.selected_list();
+        if len == 0 {
+            return;
+        }
+        let i = match table_state.selected() {
             Some(i) => {
-                if i >= self.items.len() - 1 {
+                if i >= len - 1 {
                     0
                 } else {
                     i + 1
@@ -125,6 +1095,229 @@ impl<'a> App<'a> {
             }
             None => 0,
         };
-        self.table_state.select(Some(i));
+        table_state.select(Some(i));
     }
+
+    pub fn setting_value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Theme => self.theme.name.clone(),
+            Setting::Language => self.language.name.clone(),
+            Setting::TestMode => self.config.test_mode.to_string(),
+            Setting::ExportFormat => self.config.export_format.name().to_string(),
+            Setting::DailyGoal => self.config.daily_goal.to_string(),
+            Setting::TextCues => on_off(self.config.text_cues),
+            Setting::LargeText => on_off(self.config.large_text),
+            Setting::ReduceMotion => on_off(self.config.reduce_motion),
+            Setting::Sounds => self.config.sounds.name().to_string(),
+            Setting::SoundPack => match select_pack(&self.sound_packs, &self.config.sound_pack) {
+                Some(pack) => pack.name.clone(),
+                None => "none".to_string(),
+            },
+            Setting::Volume => format!("{}%", self.config.volume),
+            Setting::Caret => self.config.caret.name().to_string(),
+            Setting::CaretBlink => on_off(self.config.caret_blink),
+            Setting::TapeMode => on_off(self.config.tape_mode),
+            Setting::FocusMode => on_off(self.config.focus_mode),
+            Setting::LiveStats => on_off(self.config.live_stats),
+            Setting::Stat(field) => on_off(self.config.stats_fields.contains(&field)),
+            Setting::Level => self.config.difficulty.level.name().to_string(),
+            Setting::StopOnError => self.config.difficulty.stop_on_error.name().to_string(),
+            Setting::NoBackspace => on_off(self.config.difficulty.no_backspace),
+            Setting::BlindMode => on_off(self.config.blind_mode),
+            Setting::MemoryMode => on_off(self.config.memory_mode),
+        }
+    }
+
+    /// Moves the selected setting to its next (or previous) value, applying it immediately.
+    pub fn cycle_setting(&mut self, forward: bool) {
+        let setting = match self.settings_state.selected() {
+            Some(i) => Setting::ALL[i],
+            None => return,
+        };
+
+        match setting {
+            Setting::Theme => {
+                let len = self.themes.len();
+                let current = self
+                    .themes
+                    .iter()
+                    .position(|t| t.name == self.config.theme)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                // Cycles through the picked names, what is shown may be the 16 color fallback
+                self.config.theme = self.themes[next].name.clone();
+                self.theme = select_theme(&self.themes, &self.config.theme, supports_truecolor());
+            }
+            Setting::Language => {
+                let len = self.languages.len();
+                let current = self
+                    .languages
+                    .iter()
+                    .position(|l| l.name == self.language.name)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.language = self.languages[next].name.clone();
+                self.load_language();
+            }
+            Setting::TestMode => {
+                let presets = TestMode::PRESETS;
+                let len = presets.len();
+                let current = presets
+                    .iter()
+                    .position(|m| *m == self.config.test_mode)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.test_mode = presets[next];
+                if !self.engine.is_active() {
+                    self.restart_test(true);
+                }
+            }
+            Setting::ExportFormat => {
+                let formats = ExportFormat::ALL;
+                let len = formats.len();
+                let current = formats
+                    .iter()
+                    .position(|f| *f == self.config.export_format)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.export_format = formats[next];
+            }
+            Setting::DailyGoal => {
+                let presets = DailyGoal::PRESETS;
+                let len = presets.len();
+                let current = presets
+                    .iter()
+                    .position(|g| *g == self.config.daily_goal)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.daily_goal = presets[next];
+            }
+            Setting::TextCues => self.config.text_cues = !self.config.text_cues,
+            Setting::LargeText => self.config.large_text = !self.config.large_text,
+            Setting::ReduceMotion => self.config.reduce_motion = !self.config.reduce_motion,
+            Setting::Sounds => {
+                let len = Sounds::ALL.len();
+                let current =
+                    Sounds::ALL.iter().position(|s| *s == self.config.sounds).unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.sounds = Sounds::ALL[next];
+                self.open_sound();
+            }
+            Setting::SoundPack => {
+                let len = self.sound_packs.len();
+                if len == 0 {
+                    return;
+                }
+                let current = self
+                    .sound_packs
+                    .iter()
+                    .position(|p| p.name == self.config.sound_pack)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.sound_pack = self.sound_packs[next].name.clone();
+            }
+            Setting::Volume => {
+                let len = VOLUMES.len();
+                let current = VOLUMES.iter().position(|v| *v >= self.config.volume).unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.volume = VOLUMES[next];
+            }
+            Setting::Caret => {
+                let len = CaretStyle::ALL.len();
+                let current =
+                    CaretStyle::ALL.iter().position(|c| *c == self.config.caret).unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.config.caret = CaretStyle::ALL[next];
+            }
+            Setting::CaretBlink => self.config.caret_blink = !self.config.caret_blink,
+            Setting::TapeMode => self.config.tape_mode = !self.config.tape_mode,
+            Setting::FocusMode => self.config.focus_mode = !self.config.focus_mode,
+            Setting::LiveStats => self.config.live_stats = !self.config.live_stats,
+            Setting::Stat(field) => toggle_field(&mut self.config.stats_fields, field),
+            Setting::Level => {
+                let len = Level::ALL.len();
+                let difficulty = &mut self.config.difficulty;
+                let current = Level::ALL.iter().position(|l| *l == difficulty.level).unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                difficulty.level = Level::ALL[next];
+            }
+            Setting::StopOnError => {
+                let len = StopOnError::ALL.len();
+                let difficulty = &mut self.config.difficulty;
+                let current = StopOnError::ALL
+                    .iter()
+                    .position(|s| *s == difficulty.stop_on_error)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                difficulty.stop_on_error = StopOnError::ALL[next];
+            }
+            Setting::NoBackspace => {
+                self.config.difficulty.no_backspace = !self.config.difficulty.no_backspace
+            }
+            Setting::BlindMode => self.config.blind_mode = !self.config.blind_mode,
+            Setting::MemoryMode => self.config.memory_mode = !self.config.memory_mode,
+        }
+
+        // A test that has not started yet takes the new rules, one under way keeps its own
+        if self.engine.state == TypingTestState::NotStarted {
+            self.engine.difficulty = self.config.difficulty;
+        }
+        self.save_config();
+    }
+}
+
+fn on_off(value: bool) -> String {
+    match value {
+        true => "on".to_string(),
+        false => "off".to_string(),
+    }
+}
+
+/// The data for tui-rs bar chart is required to be a tuple of an &str a u64 which is why leaking is required
+fn chart_entry<'a>(result: &WpmResult) -> (&'a str, u64) {
+    (Box::leak(result.date_time.to_string().into_boxed_str()) as &str, result.awpm as u64)
 }
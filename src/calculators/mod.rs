@@ -1,2 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod calculators;
+#[allow(clippy::module_inception)]
 mod tests;
\ No newline at end of file
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
     use crate::calculators::calculators::*;
-    use super::*;
 
     #[test]
     fn test_wpm() {
@@ -0,0 +1,231 @@
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use crate::error::ErrorKind;
+use crate::export::export::{export, write, ExportFormat};
+use crate::history::history::{Query, Store};
+use crate::import::import::{import_file, ImportSource, Mapping};
+
+pub const USAGE: &str = concat!(
+    "Usage: ",
+    env!("CARGO_PKG_NAME"),
+    " [--profile <NAME>] [COMMAND]
+
+Without a command the typing app starts.
+
+Options:
+    --profile <NAME>                Use the settings and history of a profile, created
+                                    when the app starts with a new name. The last used
+                                    profile by default
+
+Commands:
+    plain     Run typing tests as plain lines of text, for screen readers
+    export    Write the history as CSV, JSON or a Markdown report
+    import    Add results from a CSV file exported by another typing tool
+    help      Show this message
+
+Export options:
+    --format <csv|json|markdown>    Output format, csv by default
+    --output <PATH>                 File to write, standard output by default
+    --from <YYYY-MM-DD>             Only results taken on or after the day
+    --to <YYYY-MM-DD>               Only results taken on or before the day
+    --mode <MODE>                   Only results of a test mode, e.g. \"time 30\"
+    --language <NAME>               Only results typed in a language
+    --layout <NAME>                 Only results typed on a keyboard layout
+    --top <N>                       Only the N fastest results
+
+Import options:
+    import <FILE>                   The CSV file to read
+    --source <NAME>                 monkeytype, typeracer or csv, guessed by default
+    --map <FIELD>=<COLUMN>          Column holding a field of a csv import, repeatable.
+                                    Fields are date_time, wpm, awpm, accuracy, duration,
+                                    mode, language, layout and source
+    --percent-accuracy              Accuracy of a csv import is a percentage, not a
+                                    fraction
+"
+);
+
+/// The command along with the options that apply to every command.
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub profile: Option<String>,
+    pub command: Command,
+}
+
+impl Args {
+    /// Parses the arguments following the program name. `--profile` may come anywhere.
+    pub fn parse<I>(args: I) -> Result<Args, ErrorKind>
+    where I: IntoIterator<Item = String> {
+        let mut profile = None;
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--profile" => {
+                    profile = Some(args.next().ok_or_else(|| {
+                        ErrorKind::InvalidArgument("--profile needs a value".to_string())
+                    })?)
+                }
+                _ => rest.push(arg),
+            }
+        }
+
+        Ok(Args {
+            profile,
+            command: Command::parse(rest)?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run,
+    /// Typing tests as plain lines of text instead of the full screen interface.
+    Plain,
+    Help,
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportArgs {
+    pub format: ExportFormat,
+    /// Standard output when not set.
+    pub output: Option<PathBuf>,
+    pub query: Query,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportArgs {
+    pub path: PathBuf,
+    /// Guessed from the file when not set.
+    pub source: Option<ImportSource>,
+}
+
+impl Command {
+    /// Parses the arguments following the program name.
+    pub fn parse<I>(args: I) -> Result<Command, ErrorKind>
+    where I: IntoIterator<Item = String> {
+        let mut args = args.into_iter();
+        let command = match args.next().as_deref() {
+            None => return Ok(Command::Run),
+            Some("help" | "-h" | "--help") => Command::Help,
+            Some("plain") => Command::Plain,
+            Some("export") => Command::Export(ExportArgs::parse(&mut args)?),
+            Some("import") => Command::Import(ImportArgs::parse(&mut args)?),
+            Some(other) => {
+                return Err(ErrorKind::InvalidArgument(format!("unknown command {}", other)))
+            }
+        };
+
+        match args.next() {
+            Some(extra) => Err(ErrorKind::InvalidArgument(format!("unexpected {}", extra))),
+            None => Ok(command),
+        }
+    }
+}
+
+impl ExportArgs {
+    fn parse(args: &mut impl Iterator<Item = String>) -> Result<Self, ErrorKind> {
+        let mut export = ExportArgs {
+            format: ExportFormat::default(),
+            output: None,
+            query: Query::default(),
+        };
+
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ErrorKind::InvalidArgument(format!("{} needs a value", flag)))
+            };
+            match flag.as_str() {
+                "--format" => export.format = value()?.parse()?,
+                "--output" => export.output = Some(PathBuf::from(value()?)),
+                "--from" => export.query.from = Some(parse_day(&value()?)?),
+                // The whole day is included
+                "--to" => export.query.to = Some(parse_day(&value()?)? + Duration::days(1)),
+                "--mode" => export.query.mode = Some(value()?.parse()?),
+                "--language" => export.query.language = Some(value()?),
+                "--layout" => export.query.layout = Some(value()?),
+                "--top" => export.query.top = Some(value()?.parse()?),
+                _ => return Err(ErrorKind::InvalidArgument(format!("unknown option {}", flag))),
+            }
+        }
+
+        Ok(export)
+    }
+
+    /// Writes the matching results of `history` to the output.
+    pub fn run(&self, history: &dyn Store) -> Result<(), ErrorKind> {
+        let content = export(&history.query(&self.query)?, self.format)?;
+        match &self.output {
+            Some(path) => write(path, &content),
+            None => Ok(std::io::stdout().write_all(content.as_bytes())?),
+        }
+    }
+}
+
+impl ImportArgs {
+    fn parse(args: &mut impl Iterator<Item = String>) -> Result<Self, ErrorKind> {
+        let mut path = None;
+        let mut source = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ErrorKind::InvalidArgument(format!("{} needs a value", arg)))
+            };
+            match arg.as_str() {
+                "--source" => source = Some(value()?.parse()?),
+                "--map" => {
+                    let value = value()?;
+                    let (field, column) = value.split_once('=').ok_or_else(|| {
+                        ErrorKind::InvalidArgument(format!("{} is not FIELD=COLUMN", value))
+                    })?;
+                    csv_mapping(&mut source, &arg)?.set(field, column)?;
+                }
+                "--percent-accuracy" => csv_mapping(&mut source, &arg)?.percent_accuracy = true,
+                _ if arg.starts_with("--") => {
+                    return Err(ErrorKind::InvalidArgument(format!("unknown option {}", arg)))
+                }
+                _ if path.is_none() => path = Some(PathBuf::from(arg)),
+                _ => return Err(ErrorKind::InvalidArgument(format!("unexpected {}", arg))),
+            }
+        }
+
+        Ok(ImportArgs {
+            path: path
+                .ok_or_else(|| ErrorKind::InvalidArgument("import needs a file".to_string()))?,
+            source,
+        })
+    }
+
+    /// Adds the results of the file to `history` and prints what happened.
+    pub fn run(&self, history: &mut dyn Store) -> Result<(), ErrorKind> {
+        let report = import_file(history, &self.path, self.source.clone())?;
+        println!("{}", report);
+        Ok(())
+    }
+}
+
+/// The mapping of a csv import that `flag` changes, making the import a csv one when no source
+/// was given.
+fn csv_mapping<'a>(
+    source: &'a mut Option<ImportSource>,
+    flag: &str,
+) -> Result<&'a mut Mapping, ErrorKind> {
+    match source.get_or_insert_with(|| ImportSource::Csv(Mapping::default())) {
+        ImportSource::Csv(mapping) => Ok(mapping),
+        _ => Err(ErrorKind::InvalidArgument(format!("{} only applies to csv imports", flag))),
+    }
+}
+
+/// The local midnight starting the `YYYY-MM-DD` day.
+fn parse_day(day: &str) -> Result<DateTime<Local>, ErrorKind> {
+    let invalid = || ErrorKind::InvalidArgument(format!("{} is not a YYYY-MM-DD date", day));
+    let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| invalid())?;
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?)
+        .earliest()
+        .ok_or_else(invalid)
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod cli;
+#[allow(clippy::module_inception)]
+mod tests;
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use crate::cli::cli::{Args, Command, ExportArgs, ImportArgs};
+    use crate::engine::engine::TestMode;
+    use crate::export::export::ExportFormat;
+    use crate::history::history::Query;
+    use crate::import::import::{ImportSource, Mapping};
+
+    fn parse(args: &[&str]) -> Result<Command, crate::error::ErrorKind> {
+        Command::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&[]).unwrap(), Command::Run);
+        assert_eq!(parse(&["--help"]).unwrap(), Command::Help);
+        assert_eq!(parse(&["plain"]).unwrap(), Command::Plain);
+        assert!(parse(&["play"]).is_err());
+        assert!(parse(&["help", "export"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let args = |args: &[&str]| Args::parse(args.iter().map(|a| a.to_string()));
+
+        let parsed = args(&["--profile", "sam"]).unwrap();
+        assert_eq!(parsed.profile.as_deref(), Some("sam"));
+        assert_eq!(parsed.command, Command::Run);
+
+        let parsed = args(&["export", "--top", "3", "--profile", "sam"]).unwrap();
+        assert_eq!(parsed.profile.as_deref(), Some("sam"));
+        assert!(matches!(parsed.command, Command::Export(_)));
+
+        assert_eq!(args(&["help"]).unwrap().profile, None);
+        assert!(args(&["--profile"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let command = parse(&[
+            "export", "--format", "md", "--output", "out.md", "--from", "2024-03-01", "--to",
+            "2024-03-31", "--mode", "time 30", "--top", "5",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            command,
+            Command::Export(ExportArgs {
+                format: ExportFormat::Markdown,
+                output: Some("out.md".into()),
+                query: Query {
+                    from: Some(Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()),
+                    to: Some(Local.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap()),
+                    mode: Some(TestMode::Time(30)),
+                    top: Some(5),
+                    ..Query::default()
+                },
+            })
+        );
+
+        assert!(parse(&["export", "--format", "xml"]).is_err());
+        assert!(parse(&["export", "--from", "March"]).is_err());
+        assert!(parse(&["export", "--top"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_import() {
+        let command = parse(&["import", "runs.csv", "--percent-accuracy"]).unwrap();
+        let mut mapping = Mapping::default();
+        mapping.percent_accuracy = true;
+        assert_eq!(
+            command,
+            Command::Import(ImportArgs {
+                path: "runs.csv".into(),
+                source: Some(ImportSource::Csv(mapping)),
+            })
+        );
+
+        assert!(parse(&["import", "runs.csv", "--source", "typeracer", "--percent-accuracy"])
+            .is_err());
+        assert!(parse(&["import", "--map", "wpm"]).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::engine::engine::{Difficulty, TestMode};
+use crate::error::ErrorKind;
+use crate::export::export::ExportFormat;
+use crate::goals::goals::DailyGoal;
+use crate::history::history::HistoryBackend;
+use crate::keymap::keymap::KeymapConfig;
+use crate::sound::sound::{Sounds, DEFAULT_SOUND_PACK};
+use crate::theme::theme::{supports_truecolor, DEFAULT_THEME, FALLBACK_THEME};
+use crate::ui::caret::CaretStyle;
+use crate::ui::stats_bar::StatField;
+
+/// User settings persisted as TOML. Keys missing from the file keep their default value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub theme: String,
+    pub test_mode: TestMode,
+    pub history: HistoryBackend,
+    /// Language of the wordlist, saved with each result.
+    pub language: String,
+    /// Keyboard layout typed on, saved with each result.
+    pub layout: String,
+    /// Format written by the in-app export.
+    pub export_format: ExportFormat,
+    /// Practice to do each day, e.g. `"10 minutes"` or `"5 tests"`.
+    pub daily_goal: DailyGoal,
+    /// WPM a result is congratulated for reaching the first time.
+    pub milestones: Vec<u64>,
+    /// Marks mistakes with underlines, bold and strikethrough as well as color.
+    pub text_cues: bool,
+    /// Spaces letters and lines of the words apart to make them easier to read.
+    pub large_text: bool,
+    /// Turns the words a page at a time instead of scrolling them line by line.
+    pub reduce_motion: bool,
+    /// Which typing sounds play, needs the `audio` cargo feature for more than the bell.
+    pub sounds: Sounds,
+    /// Directory of `resource/sounds` the sounds are taken from.
+    pub sound_pack: String,
+    /// Volume of the sounds in percent.
+    pub volume: u8,
+    /// How the caret is drawn in the words being typed.
+    pub caret: CaretStyle,
+    /// Blinks the caret while no keys are typed, unless motion is reduced.
+    pub caret_blink: bool,
+    /// Shows the words on a single line that scrolls past the caret.
+    pub tape_mode: bool,
+    /// Hides everything but the words while a test runs.
+    pub focus_mode: bool,
+    /// Shows the live stats bar while typing, turned off to hide every field at once.
+    pub live_stats: bool,
+    /// Fields of the live stats bar.
+    pub stats_fields: Vec<StatField>,
+    /// Leaves mistakes unmarked until the results.
+    pub blind_mode: bool,
+    /// Shows a few words at a time, briefly, so they are typed from memory.
+    pub memory_mode: bool,
+    /// What mistakes do to a test, written as a table so kept after the plain values.
+    pub difficulty: Difficulty,
+    /// Per screen key binding overrides, e.g. `[keys.menu] quit = ["q", "ctrl-c"]`.
+    /// Kept last since TOML needs plain values written before tables.
+    #[serde(skip_serializing_if = "KeymapConfig::is_empty")]
+    pub keys: KeymapConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: if supports_truecolor() {
+                DEFAULT_THEME.to_string()
+            } else {
+                FALLBACK_THEME.to_string()
+            },
+            test_mode: TestMode::default(),
+            history: HistoryBackend::default(),
+            language: "english".to_string(),
+            layout: "qwerty".to_string(),
+            export_format: ExportFormat::default(),
+            daily_goal: DailyGoal::default(),
+            milestones: vec![40, 60, 80, 100, 120, 150],
+            text_cues: false,
+            large_text: false,
+            reduce_motion: false,
+            sounds: Sounds::default(),
+            sound_pack: DEFAULT_SOUND_PACK.to_string(),
+            volume: 50,
+            caret: CaretStyle::default(),
+            caret_blink: false,
+            tape_mode: false,
+            focus_mode: false,
+            live_stats: true,
+            stats_fields: StatField::DEFAULT.to_vec(),
+            blind_mode: false,
+            memory_mode: false,
+            difficulty: Difficulty::default(),
+            keys: KeymapConfig::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config at `path`, using the defaults when there is none yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ErrorKind> {
+        if !path.as_ref().exists() {
+            return Ok(Config::default());
+        }
+        Config::from_file(path)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ErrorKind> {
+        let path = path.as_ref();
+        let config_error = |e: ErrorKind| ErrorKind::ConfigError(path.to_path_buf(), Box::new(e));
+        let content = std::fs::read_to_string(path).map_err(|e| config_error(e.into()))?;
+        Config::from_toml(&content).map_err(config_error)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, ErrorKind> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ErrorKind> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ErrorKind> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_toml()?)
+            .map_err(|e| ErrorKind::FileError(path.to_path_buf(), e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use crate::config::config::Config;
+    use crate::engine::engine::{Difficulty, Level};
+    use crate::goals::goals::DailyGoal;
+    use crate::history::history::HistoryBackend;
+    use crate::test_util::temp_path;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_config_roundtrip() {
+        let mut menu = BTreeMap::new();
+        menu.insert("quit".to_string(), vec!["x".to_string()]);
+
+        let mut config = Config {
+            theme: "nord".to_string(),
+            history: HistoryBackend::Sqlite,
+            layout: "dvorak".to_string(),
+            daily_goal: DailyGoal::Tests(3),
+            milestones: vec![50, 70],
+            difficulty: Difficulty {
+                level: Level::Master,
+                no_backspace: true,
+                ..Difficulty::default()
+            },
+            ..Config::default()
+        };
+        config.keys.insert("menu".to_string(), menu);
+
+        let parsed = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let path = temp_path("bunbuntype_test_config.toml");
+        std::fs::write(&path, "theme = 3").unwrap();
+
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().starts_with("Config "));
+        assert!(err.to_string().contains("bunbuntype_test_config.toml"));
+        assert_eq!(Config::load(path.with_extension("missing")).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let parsed = Config::from_toml("").unwrap();
+        assert_eq!(parsed, Config::default());
+        assert_eq!(parsed.history, HistoryBackend::Json);
+
+        assert!(Config::from_toml("history = \"csv\"").is_err());
+        assert!(Config::from_toml("daily_goal = \"often\"").is_err());
+    }
+}
@@ -0,0 +1,663 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::calculators::calculators::{calculate_accuracy, calculate_awpm, calculate_wpm};
+use crate::error::ErrorKind;
+use crate::serializers::wpm_results::WpmResult;
+
+/// Words kept queued ahead of the typist in time mode.
+pub const WORD_BUFFER: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TestMode {
+    /// Type for this many seconds.
+    Time(u64),
+    /// Type this many words.
+    Words(usize),
+}
+
+impl TestMode {
+    pub const PRESETS: [TestMode; 8] = [
+        TestMode::Time(15),
+        TestMode::Time(30),
+        TestMode::Time(60),
+        TestMode::Time(120),
+        TestMode::Words(10),
+        TestMode::Words(25),
+        TestMode::Words(50),
+        TestMode::Words(100),
+    ];
+}
+
+impl Default for TestMode {
+    fn default() -> Self {
+        TestMode::Time(30)
+    }
+}
+
+impl std::fmt::Display for TestMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TestMode::Time(secs) => write!(f, "time {}", secs),
+            TestMode::Words(count) => write!(f, "words {}", count),
+        }
+    }
+}
+
+impl std::str::FromStr for TestMode {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(' ') {
+            Some(("time", secs)) => Ok(TestMode::Time(secs.parse()?)),
+            Some(("words", count)) => Ok(TestMode::Words(count.parse()?)),
+            _ => Err(ErrorKind::InvalidMode(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for TestMode {
+    type Error = ErrorKind;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TestMode> for String {
+    fn from(mode: TestMode) -> Self {
+        mode.to_string()
+    }
+}
+
+/// How harshly mistakes are punished.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    #[default]
+    Normal,
+    /// Submitting a wrong word fails the test.
+    Expert,
+    /// Any wrong key fails the test.
+    Master,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Normal, Level::Expert, Level::Master];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Normal => "normal",
+            Level::Expert => "expert",
+            Level::Master => "master",
+        }
+    }
+
+    /// Why a test at this level failed.
+    pub fn failure(&self) -> &'static str {
+        match self {
+            Level::Normal => "a rule was broken",
+            Level::Expert => "a wrong word was submitted in expert mode",
+            Level::Master => "a wrong key was typed in master mode",
+        }
+    }
+}
+
+/// Where a mistake holds the typist up until it is put right.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StopOnError {
+    #[default]
+    Off,
+    /// A wrong key is not typed, so nothing gets past the first wrong letter.
+    Letter,
+    /// Space does not submit a wrong word.
+    Word,
+}
+
+impl StopOnError {
+    pub const ALL: [StopOnError; 3] = [StopOnError::Off, StopOnError::Letter, StopOnError::Word];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopOnError::Off => "off",
+            StopOnError::Letter => "letter",
+            StopOnError::Word => "word",
+        }
+    }
+}
+
+/// The rules a test is typed under, saved with its result.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Difficulty {
+    pub level: Level,
+    pub stop_on_error: StopOnError,
+    /// Mistakes cannot be taken back.
+    pub no_backspace: bool,
+}
+
+impl Difficulty {
+    pub fn is_default(&self) -> bool {
+        *self == Difficulty::default()
+    }
+}
+
+/// The rules that are not the default, e.g. `"master, stop on letter, no backspace"`.
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut rules = Vec::new();
+        if self.level != Level::Normal {
+            rules.push(self.level.name().to_string());
+        }
+        if self.stop_on_error != StopOnError::Off {
+            rules.push(format!("stop on {}", self.stop_on_error.name()));
+        }
+        if self.no_backspace {
+            rules.push("no backspace".to_string());
+        }
+        match rules.is_empty() {
+            true => write!(f, "normal"),
+            false => write!(f, "{}", rules.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypingTestState {
+    /// Words are shown, the clock starts on the first keystroke.
+    NotStarted,
+    Running,
+    /// The clock is frozen, time spent here does not count towards WPM.
+    Paused,
+    /// The test ran to completion and produced a result.
+    End,
+    /// The test was given up, nothing was recorded.
+    Abandoned,
+    /// A mistake broke the rules of an expert or master test, nothing was recorded.
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KeyKind {
+    Char(char),
+    Backspace,
+}
+
+/// A key press with the active (unpaused) time at which it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keystroke {
+    #[serde(with = "millis")]
+    pub at: Duration,
+    pub key: KeyKind,
+    pub correct: bool,
+    /// Typed wrong while stopped on a mistake, so it never made it into the text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub blocked: bool,
+}
+
+/// Keystroke times are saved in whole milliseconds to keep replays small.
+mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(at: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(at.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// Speed and mistakes at one second of a test.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Seconds into the test, a fraction for the last sample of a words test.
+    pub second: f64,
+    /// WPM of the correct keystrokes so far.
+    pub wpm: f64,
+    /// WPM of every keystroke since the previous sample.
+    pub raw: f64,
+    /// Mistakes since the previous sample.
+    pub errors: u32,
+}
+
+/// How a test is going while it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LiveStats {
+    /// Time left in time mode, `None` in words mode.
+    pub time_left: Option<Duration>,
+    /// Words left to type in words mode.
+    pub words_left: usize,
+    /// WPM of the correct keystrokes so far.
+    pub wpm: f64,
+    /// WPM of every keystroke so far.
+    pub raw: f64,
+    pub accuracy: f64,
+    /// Keystrokes typed wrong so far.
+    pub errors: usize,
+}
+
+/// Adds a typed character to `input`. Characters are kept composed, so an accent typed as
+/// a separate combining mark joins the letter before it into one grapheme.
+pub fn push_char(input: &mut String, c: char) {
+    input.push(c);
+    if !c.is_ascii() {
+        *input = input.nfc().collect();
+    }
+}
+
+/// Removes the last grapheme of `input`, returning false if it was empty.
+pub fn pop_grapheme(input: &mut String) -> bool {
+    match input.grapheme_indices(true).next_back() {
+        Some((start, _)) => {
+            input.truncate(start);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether the `typed` grapheme is `expected` or the start of it, like a letter still
+/// waiting for the accent that completes it.
+pub fn grapheme_matches(expected: &str, typed: &str) -> bool {
+    let expected = expected.nfd().collect::<String>();
+    let typed = typed.nfd().collect::<String>();
+    !typed.is_empty() && expected.starts_with(&typed)
+}
+
+pub struct TypingEngine {
+    pub mode: TestMode,
+    pub difficulty: Difficulty,
+    pub state: TypingTestState,
+    /// Words to type, composed the same way as the input.
+    pub words: Vec<String>,
+    /// Words already submitted with space, in order.
+    pub typed: Vec<String>,
+    pub input: String,
+    pub keystrokes: Vec<Keystroke>,
+    /// One sample per second of running time.
+    pub timeline: Vec<Sample>,
+    /// Keystrokes already counted in the timeline.
+    sampled: usize,
+    started_at: Option<Instant>,
+    paused_at: Option<Instant>,
+    paused_for: Duration,
+    ended_after: Option<Duration>,
+}
+
+impl TypingEngine {
+    pub fn new(mode: TestMode, words: Vec<String>) -> Self {
+        Self {
+            mode,
+            difficulty: Difficulty::default(),
+            state: TypingTestState::NotStarted,
+            words: words.into_iter().map(|w| w.nfc().collect()).collect(),
+            typed: Vec::new(),
+            input: String::new(),
+            keystrokes: Vec::new(),
+            timeline: Vec::new(),
+            sampled: 0,
+            started_at: None,
+            paused_at: None,
+            paused_for: Duration::ZERO,
+            ended_after: None,
+        }
+    }
+
+    /// Starts over on the same words.
+    pub fn restart(&mut self) {
+        let words = std::mem::take(&mut self.words);
+        let difficulty = self.difficulty;
+        *self = TypingEngine::new(self.mode, words);
+        self.difficulty = difficulty;
+    }
+
+    /// Starts over on new words.
+    pub fn reset(&mut self, words: Vec<String>) {
+        self.words = words;
+        self.restart();
+    }
+
+    /// Queues more words, used to keep time mode tests from running dry.
+    pub fn extend(&mut self, words: Vec<String>) {
+        self.words.extend(words.into_iter().map(|w| w.nfc().collect::<String>()));
+    }
+
+    pub fn words_left(&self) -> usize {
+        self.words.len().saturating_sub(self.typed.len())
+    }
+
+    pub fn current_word(&self) -> Option<&str> {
+        self.words.get(self.typed.len()).map(|w| w.as_str())
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, TypingTestState::Running | TypingTestState::Paused)
+    }
+
+    /// Time spent running, excluding pauses.
+    pub fn elapsed_at(&self, now: Instant) -> Duration {
+        if let Some(ended) = self.ended_after {
+            return ended;
+        }
+        match self.started_at {
+            Some(started) => {
+                let until = self.paused_at.unwrap_or(now);
+                until.saturating_duration_since(started).saturating_sub(self.paused_for)
+            }
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Time left in time mode, `None` in words mode.
+    pub fn remaining_at(&self, now: Instant) -> Option<Duration> {
+        match self.mode {
+            TestMode::Time(secs) => {
+                Some(Duration::from_secs(secs).saturating_sub(self.elapsed_at(now)))
+            }
+            TestMode::Words(_) => None,
+        }
+    }
+
+    pub fn pause_at(&mut self, now: Instant) {
+        if self.state == TypingTestState::Running {
+            self.paused_at = Some(now);
+            self.state = TypingTestState::Paused;
+        }
+    }
+
+    pub fn resume_at(&mut self, now: Instant) {
+        if let (TypingTestState::Paused, Some(paused)) = (self.state, self.paused_at.take()) {
+            self.paused_for += now.saturating_duration_since(paused);
+            self.state = TypingTestState::Running;
+        }
+    }
+
+    /// Gives up on the test without producing a result.
+    pub fn abandon(&mut self) {
+        if self.state != TypingTestState::End {
+            self.state = TypingTestState::Abandoned;
+        }
+    }
+
+    pub fn type_char_at(&mut self, c: char, now: Instant) {
+        // Nothing to submit yet, ignore stray spaces
+        if c.is_whitespace() && self.input.is_empty() {
+            return;
+        }
+
+        match self.state {
+            TypingTestState::NotStarted => {
+                self.started_at = Some(now);
+                self.state = TypingTestState::Running;
+            }
+            TypingTestState::Running => {
+                if self.tick_at(now) {
+                    return;
+                }
+            }
+            _ => return,
+        }
+
+        let target = self.current_word().unwrap_or_default().to_string();
+        let stop = self.difficulty.stop_on_error;
+
+        if c.is_whitespace() {
+            let correct = self.input == target;
+            if !correct && stop != StopOnError::Off {
+                self.block(' ', now);
+                self.tick_at(now);
+                return;
+            }
+            let input = std::mem::take(&mut self.input);
+            self.record(KeyKind::Char(' '), correct, now);
+            self.typed.push(input);
+            if !correct && self.difficulty.level != Level::Normal {
+                self.fail(now);
+                return;
+            }
+        } else {
+            // Graphemes are the typing units, a key that only starts one is right so far
+            let before = self.input.clone();
+            push_char(&mut self.input, c);
+            let index = self.input.graphemes(true).count() - 1;
+            let expected = target.graphemes(true).nth(index);
+            let typed = self.input.graphemes(true).next_back();
+            let correct = match (expected, typed) {
+                (Some(expected), Some(typed)) => grapheme_matches(expected, typed),
+                _ => false,
+            };
+            if !correct && stop == StopOnError::Letter {
+                self.input = before;
+                self.block(c, now);
+                self.tick_at(now);
+                return;
+            }
+            self.record(KeyKind::Char(c), correct, now);
+            if !correct && self.difficulty.level == Level::Master {
+                self.fail(now);
+                return;
+            }
+
+            // The last word of a words test counts as soon as it is typed correctly
+            if self.words_left() == 1
+                && matches!(self.mode, TestMode::Words(_))
+                && self.input == target
+            {
+                let input = std::mem::take(&mut self.input);
+                self.typed.push(input);
+            }
+        }
+
+        self.tick_at(now);
+    }
+
+    pub fn backspace_at(&mut self, now: Instant) {
+        if self.difficulty.no_backspace {
+            return;
+        }
+        if self.state == TypingTestState::Running && pop_grapheme(&mut self.input) {
+            self.record(KeyKind::Backspace, true, now);
+        }
+    }
+
+    fn record(&mut self, key: KeyKind, correct: bool, now: Instant) {
+        self.keystrokes.push(Keystroke {
+            at: self.elapsed_at(now),
+            key,
+            correct,
+            blocked: false,
+        });
+    }
+
+    /// Records a key typed wrong while stopped on a mistake. It counts as a mistake, fails
+    /// a master test, but is not typed.
+    fn block(&mut self, c: char, now: Instant) {
+        self.keystrokes.push(Keystroke {
+            at: self.elapsed_at(now),
+            key: KeyKind::Char(c),
+            correct: false,
+            blocked: true,
+        });
+        if self.difficulty.level == Level::Master {
+            self.fail(now);
+        }
+    }
+
+    fn fail(&mut self, now: Instant) {
+        self.ended_after = Some(self.elapsed_at(now));
+        self.state = TypingTestState::Failed;
+    }
+
+    /// Ends the test once its time or words are used up. Returns true if it ended now.
+    pub fn tick_at(&mut self, now: Instant) -> bool {
+        if self.state != TypingTestState::Running {
+            return false;
+        }
+
+        let done = match self.mode {
+            TestMode::Time(_) => self.remaining_at(now) == Some(Duration::ZERO),
+            TestMode::Words(_) => self.words_left() == 0,
+        };
+
+        let elapsed = match self.mode {
+            TestMode::Time(secs) => self.elapsed_at(now).min(Duration::from_secs(secs)),
+            TestMode::Words(_) => self.elapsed_at(now),
+        };
+        while (self.timeline.len() + 1) as f64 <= elapsed.as_secs_f64() {
+            self.take_sample(Duration::from_secs(self.timeline.len() as u64 + 1), false);
+        }
+
+        if done {
+            // What was typed after the last whole second makes a shorter last sample
+            let last = self.timeline.last().map_or(0.0, |s| s.second);
+            if elapsed.as_secs_f64() > last {
+                self.take_sample(elapsed, true);
+            }
+            self.ended_after = Some(elapsed);
+            self.state = TypingTestState::End;
+        }
+
+        done
+    }
+
+    /// Adds a sample ending at `end` of the keystrokes since the previous one. Keystrokes
+    /// at `end` itself belong to the next sample, unless this is the `last` one.
+    fn take_sample(&mut self, end: Duration, last: bool) {
+        let start = self.timeline.last().map_or(0.0, |s| s.second);
+        let count = self.keystrokes[self.sampled..]
+            .iter()
+            .take_while(|k| last || k.at < end)
+            .count();
+        let window = &self.keystrokes[self.sampled..self.sampled + count];
+        self.sampled += count;
+
+        let chars = window.iter().filter(|k| matches!(k.key, KeyKind::Char(_))).count();
+        let errors = window.iter().filter(|k| !k.correct).count();
+        let correct = self.keystrokes[..self.sampled]
+            .iter()
+            .filter(|k| matches!(k.key, KeyKind::Char(_)) && k.correct)
+            .count();
+        let wpm = |chars: usize, seconds: f64| match seconds > 0.0 {
+            true => calculate_wpm(chars as f32, (seconds / 60.0) as f32) as f64,
+            false => 0.0,
+        };
+
+        self.timeline.push(Sample {
+            second: end.as_secs_f64(),
+            wpm: wpm(correct, end.as_secs_f64()),
+            raw: wpm(chars, end.as_secs_f64() - start),
+            errors: errors as u32,
+        });
+    }
+
+    pub fn tick(&mut self) -> bool {
+        self.tick_at(Instant::now())
+    }
+
+    pub fn correct_words(&self) -> usize {
+        self.typed
+            .iter()
+            .zip(self.words.iter())
+            .filter(|(typed, word)| typed == word)
+            .count()
+    }
+
+    pub fn incorrect_words(&self) -> usize {
+        self.typed.len() - self.correct_words()
+    }
+
+    /// Each word typed so far along with whether it was typed right.
+    pub fn word_map(&self) -> Vec<(String, bool)> {
+        self.typed
+            .iter()
+            .zip(self.words.iter())
+            .map(|(typed, word)| (word.clone(), typed == word))
+            .collect()
+    }
+
+    /// The words typed so far including the one in progress, separated by spaces.
+    pub fn typed_text(&self) -> String {
+        let mut words = self.typed.clone();
+        if !self.input.is_empty() {
+            words.push(self.input.clone());
+        }
+        words.join(" ")
+    }
+
+    /// Character keystrokes (including spaces), as `(total, correct)`.
+    pub fn char_counts(&self) -> (usize, usize) {
+        self.keystrokes
+            .iter()
+            .filter(|k| matches!(k.key, KeyKind::Char(_)))
+            .fold((0, 0), |(total, correct), k| {
+                (total + 1, correct + k.correct as usize)
+            })
+    }
+
+    pub fn wpm_at(&self, now: Instant) -> f64 {
+        let minutes = self.elapsed_at(now).as_secs_f32() / 60.0;
+        if minutes == 0.0 {
+            return 0.0;
+        }
+        let (total, _) = self.char_counts();
+        calculate_wpm(total as f32, minutes) as f64
+    }
+
+    pub fn live_stats_at(&self, now: Instant) -> LiveStats {
+        let minutes = self.elapsed_at(now).as_secs_f32() / 60.0;
+        let wpm = |chars: usize| match minutes > 0.0 {
+            true => calculate_wpm(chars as f32, minutes) as f64,
+            false => 0.0,
+        };
+        let (total, correct) = self.char_counts();
+        LiveStats {
+            time_left: self.remaining_at(now),
+            words_left: match self.mode {
+                TestMode::Time(_) => 0,
+                TestMode::Words(count) => count.saturating_sub(self.typed.len()),
+            },
+            wpm: wpm(correct),
+            raw: wpm(total),
+            accuracy: self.accuracy(),
+            errors: self.keystrokes.iter().filter(|k| !k.correct).count(),
+        }
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        match self.char_counts() {
+            (0, _) => 0.0,
+            (total, correct) => calculate_accuracy(total as f32, correct as f32) as f64,
+        }
+    }
+
+    /// The result of a finished test, `None` if it has not ended.
+    pub fn result(&self) -> Option<WpmResult> {
+        if self.state != TypingTestState::End {
+            return None;
+        }
+        let elapsed = self.ended_after?;
+        let minutes = elapsed.as_secs_f32() / 60.0;
+        let (total, correct) = self.char_counts();
+        let awpm = if minutes > 0.0 && total > 0 {
+            calculate_awpm(total as f32, minutes, correct as f32) as f64
+        } else {
+            0.0
+        };
+
+        let mut result = WpmResult::new(
+            self.wpm_at(Instant::now()),
+            self.accuracy(),
+            minutes as f64,
+            awpm,
+        );
+        result.mode = Some(self.mode);
+        result.difficulty = self.difficulty;
+        result.timeline = self.timeline.clone();
+        Some(result)
+    }
+}
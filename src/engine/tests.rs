@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests {
+    use crate::engine::engine::*;
+    use std::time::{Duration, Instant};
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn type_str(engine: &mut TypingEngine, text: &str, at: Instant) {
+        for c in text.chars() {
+            engine.type_char_at(c, at);
+        }
+    }
+
+    #[test]
+    fn test_mode_parse() {
+        assert_eq!("time 60".parse::<TestMode>().unwrap(), TestMode::Time(60));
+        assert_eq!("words 25".parse::<TestMode>().unwrap(), TestMode::Words(25));
+        assert_eq!(TestMode::Words(10).to_string(), "words 10");
+        assert!("laps 3".parse::<TestMode>().is_err());
+    }
+
+    #[test]
+    fn test_pause_excluded_from_elapsed() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Time(30), words(&["a", "b"]));
+
+        engine.type_char_at('a', start);
+        engine.pause_at(start + Duration::from_secs(5));
+        assert_eq!(engine.state, TypingTestState::Paused);
+        assert_eq!(engine.elapsed_at(start + Duration::from_secs(60)), Duration::from_secs(5));
+
+        engine.resume_at(start + Duration::from_secs(60));
+        assert_eq!(engine.elapsed_at(start + Duration::from_secs(62)), Duration::from_secs(7));
+
+        // Keys are ignored while paused
+        engine.pause_at(start + Duration::from_secs(62));
+        engine.type_char_at('x', start + Duration::from_secs(63));
+        assert_eq!(engine.input, "a");
+    }
+
+    #[test]
+    fn test_words_mode_ends() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["ab", "cd"]));
+
+        type_str(&mut engine, "ax cd", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.correct_words(), 1);
+        assert_eq!(engine.incorrect_words(), 1);
+        assert_eq!(engine.char_counts(), (5, 3));
+        assert!(engine.result().is_some());
+    }
+
+    #[test]
+    fn test_time_mode_ends() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Time(15), words(&["ab", "cd"]));
+
+        type_str(&mut engine, "ab ", start);
+        assert!(!engine.tick_at(start + Duration::from_secs(14)));
+        assert!(engine.tick_at(start + Duration::from_secs(15)));
+
+        let result = engine.result().unwrap();
+        assert_eq!(result.duration, 0.25);
+        assert_eq!(result.accuracy, 1.0);
+    }
+
+    #[test]
+    fn test_restart_and_abandon() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["ab", "cd"]));
+
+        type_str(&mut engine, "ab c", start);
+        engine.restart();
+        assert_eq!(engine.state, TypingTestState::NotStarted);
+        assert_eq!(engine.words, words(&["ab", "cd"]));
+        assert!(engine.typed.is_empty() && engine.input.is_empty() && engine.keystrokes.is_empty());
+
+        type_str(&mut engine, "a", start);
+        engine.abandon();
+        assert_eq!(engine.state, TypingTestState::Abandoned);
+        assert!(engine.result().is_none());
+
+        engine.reset(words(&["ef"]));
+        assert_eq!(engine.current_word(), Some("ef"));
+    }
+
+    #[test]
+    fn test_backspace() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(1), words(&["abc"]));
+
+        type_str(&mut engine, "ax", start);
+        engine.backspace_at(start);
+        type_str(&mut engine, "bc", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.char_counts(), (4, 3));
+    }
+
+    #[test]
+    fn test_timeline() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["abc", "abc"]));
+
+        type_str(&mut engine, "ab", at(0));
+        engine.type_char_at('x', at(500));
+        engine.tick_at(at(1200));
+        assert_eq!(engine.timeline.len(), 1);
+
+        engine.backspace_at(at(1300));
+        type_str(&mut engine, "c abc", at(2500));
+        assert_eq!(engine.state, TypingTestState::End);
+
+        let timeline = &engine.result().unwrap().timeline;
+        assert_eq!(timeline.iter().map(|s| s.second).collect::<Vec<_>>(), vec![1.0, 2.0, 2.5]);
+        // Three keys in the first second, one of them a mistake
+        assert_eq!(timeline[0].raw, 36.0);
+        assert_eq!(timeline[0].errors, 1);
+        assert_eq!(timeline[0].wpm, 24.0);
+        assert_eq!(timeline[1].raw, 0.0);
+        // The last half second held the five keys that finished the test
+        assert!((timeline[2].raw - 120.0).abs() < 0.01);
+        assert!((timeline[2].wpm - 33.6).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_live_stats() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut engine = TypingEngine::new(TestMode::Words(3), words(&["ab", "cd", "ef"]));
+        let stats = engine.live_stats_at(start);
+        assert_eq!(stats, LiveStats { words_left: 3, ..LiveStats::default() });
+
+        type_str(&mut engine, "ab cx", start);
+        let stats = engine.live_stats_at(at(6));
+        assert_eq!(stats.time_left, None);
+        assert_eq!(stats.words_left, 2);
+        assert_eq!(stats.wpm, 8.0);
+        assert_eq!(stats.raw, 10.0);
+        assert!((stats.accuracy - 0.8).abs() < 1e-6);
+        assert_eq!(stats.errors, 1);
+
+        let mut engine = TypingEngine::new(TestMode::Time(15), words(&["ab"]));
+        type_str(&mut engine, "a", start);
+        assert_eq!(engine.live_stats_at(at(5)).time_left, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_stop_on_letter() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["ab", "cd"]));
+        engine.difficulty.stop_on_error = StopOnError::Letter;
+
+        // Wrong letters and an early space count as mistakes without being typed
+        type_str(&mut engine, "ax", start);
+        assert_eq!(engine.input, "a");
+        type_str(&mut engine, " b cd", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.typed, words(&["ab", "cd"]));
+        assert_eq!(engine.char_counts(), (7, 5));
+        assert_eq!(engine.keystrokes.iter().filter(|k| k.blocked).count(), 2);
+    }
+
+    #[test]
+    fn test_stop_on_word() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["ab", "cd"]));
+        engine.difficulty.stop_on_error = StopOnError::Word;
+
+        type_str(&mut engine, "ax ", start);
+        assert!(engine.typed.is_empty());
+        assert_eq!(engine.input, "ax");
+        engine.backspace_at(start);
+        type_str(&mut engine, "b cd", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.correct_words(), 2);
+    }
+
+    #[test]
+    fn test_failing_levels() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["ab", "cd"]));
+        engine.difficulty.level = Level::Expert;
+
+        // Expert lets wrong letters be fixed but fails on a wrong word
+        type_str(&mut engine, "ax", start);
+        engine.backspace_at(start);
+        type_str(&mut engine, "b c ", start);
+        assert_eq!(engine.state, TypingTestState::Failed);
+        assert!(engine.result().is_none());
+        type_str(&mut engine, "d", start);
+        assert_eq!(engine.typed, words(&["ab", "c"]));
+        assert!(engine.input.is_empty());
+
+        engine.difficulty.level = Level::Master;
+        engine.restart();
+        assert_eq!(engine.difficulty.level, Level::Master);
+        type_str(&mut engine, "ax", start);
+        assert_eq!(engine.state, TypingTestState::Failed);
+    }
+
+    #[test]
+    fn test_no_backspace() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(1), words(&["abc"]));
+        engine.difficulty.no_backspace = true;
+
+        type_str(&mut engine, "ax", start);
+        engine.backspace_at(start);
+        assert_eq!(engine.input, "ax");
+
+        engine.reset(words(&["abc"]));
+        assert!(engine.difficulty.no_backspace);
+        type_str(&mut engine, "abc", start);
+        let result = engine.result().unwrap();
+        assert_eq!(result.difficulty.to_string(), "no backspace");
+        assert_eq!(Difficulty::default().to_string(), "normal");
+    }
+
+    #[test]
+    fn test_word_map() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(3), words(&["one", "two", "six"]));
+        assert!(engine.word_map().is_empty());
+
+        type_str(&mut engine, "one tow si", start);
+        let map = engine.word_map();
+        assert_eq!(map, vec![("one".to_string(), true), ("two".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_accented_graphemes() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["café", "naïve"]));
+
+        // A dead key delivers the composed letter, a compose sequence the letter and then
+        // the combining accent; both end up as the same single grapheme
+        type_str(&mut engine, "café ", start);
+        type_str(&mut engine, "nai\u{308}", start);
+        assert_eq!(engine.input, "naï");
+        assert!(engine.keystrokes.iter().all(|k| k.correct));
+
+        // Backspace takes the whole grapheme, accent and all
+        engine.backspace_at(start);
+        assert_eq!(engine.input, "na");
+        type_str(&mut engine, "ïve", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.correct_words(), 2);
+
+        let mut engine = TypingEngine::new(TestMode::Words(1), words(&["café"]));
+        type_str(&mut engine, "cafe\u{300}", start);
+        assert_eq!(engine.input, "cafè");
+        assert!(!engine.keystrokes.last().unwrap().correct);
+    }
+
+    #[test]
+    fn test_cjk_graphemes() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["日本語", "한국어"]));
+
+        // An IME commits whole characters, each one a typing unit
+        type_str(&mut engine, "日本五", start);
+        assert_eq!(engine.char_counts(), (3, 2));
+        engine.backspace_at(start);
+        type_str(&mut engine, "語 한국어", start);
+        assert_eq!(engine.state, TypingTestState::End);
+        assert_eq!(engine.correct_words(), 2);
+    }
+
+    #[test]
+    fn test_grapheme_helpers() {
+        let mut input = String::from("e");
+        push_char(&mut input, '\u{301}');
+        assert_eq!(input, "é");
+        assert!(grapheme_matches("é", "e"));
+        assert!(grapheme_matches("é", "é"));
+        assert!(!grapheme_matches("é", "è"));
+        assert!(!grapheme_matches("e", ""));
+
+        // A family emoji is several code points joined into one grapheme
+        let mut input = String::from("hi👨‍👩‍👧");
+        assert!(pop_grapheme(&mut input));
+        assert_eq!(input, "hi");
+        input.clear();
+        assert!(!pop_grapheme(&mut input));
+    }
+}
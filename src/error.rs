@@ -1,8 +1,48 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
 pub enum ErrorKind {
     Error(Box<dyn std::error::Error>),
     IOError(std::io::Error),
     ParseError(std::num::ParseIntError),
     ParseFloatError(std::num::ParseFloatError),
+    JsonError(serde_json::Error),
+    TomlError(toml::de::Error),
+    TomlSerializeError(toml::ser::Error),
+    CsvError(csv::Error),
+    #[cfg(feature = "sqlite")]
+    SqliteError(rusqlite::Error),
+    InvalidColor(String),
+    InvalidKey(String),
+    InvalidMode(String),
+    InvalidGoal(String),
+    InvalidArgument(String),
+    InvalidProfile(String),
+    InvalidLanguage(String),
+    InvalidSound(String),
+    /// Reading or writing the file at the path failed.
+    FileError(PathBuf, std::io::Error),
+    /// The config file at the path could not be used.
+    ConfigError(PathBuf, Box<ErrorKind>),
+    /// The theme file at the path could not be used.
+    ThemeError(PathBuf, Box<ErrorKind>),
+    /// The language pack at the path could not be used.
+    LanguageError(PathBuf, Box<ErrorKind>),
+    /// The sound pack or sound file at the path could not be used.
+    SoundError(PathBuf, Box<ErrorKind>),
+    /// The audio output could not be opened.
+    #[cfg(feature = "audio")]
+    AudioError(String),
+    /// The wordlist at the path has no usable words.
+    WordlistError(PathBuf, String),
+    /// The 1-based line of the history file at the path could not be read.
+    HistoryError(PathBuf, usize, Box<ErrorKind>),
+    /// The history database at the path could not be used.
+    DatabaseError(PathBuf, Box<ErrorKind>),
+    /// The history backend was left out of this build.
+    UnsupportedBackend(String),
+    /// No saved result was found for the date.
+    MissingResult(String),
 }
 
 impl std::fmt::Display for ErrorKind {
@@ -12,6 +52,62 @@ impl std::fmt::Display for ErrorKind {
             ErrorKind::IOError(e) => write!(f, "{}", e),
             ErrorKind::ParseError(e) => write!(f, "{}", e),
             ErrorKind::ParseFloatError(e) => write!(f, "{}", e),
+            ErrorKind::JsonError(e) => write!(f, "{}", e),
+            ErrorKind::TomlError(e) => write!(f, "{}", e),
+            ErrorKind::TomlSerializeError(e) => write!(f, "{}", e),
+            ErrorKind::CsvError(e) => write!(f, "{}", e),
+            #[cfg(feature = "sqlite")]
+            ErrorKind::SqliteError(e) => write!(f, "{}", e),
+            ErrorKind::InvalidColor(c) => write!(f, "Invalid color: {}", c),
+            ErrorKind::InvalidKey(k) => write!(f, "Invalid key binding: {}", k),
+            ErrorKind::InvalidMode(m) => write!(f, "Invalid test mode: {}", m),
+            ErrorKind::InvalidGoal(g) => write!(f, "Invalid daily goal: {}", g),
+            ErrorKind::InvalidArgument(a) => write!(f, "Invalid argument: {}", a),
+            ErrorKind::InvalidProfile(p) => write!(f, "Profile {}", p),
+            ErrorKind::InvalidLanguage(l) => write!(f, "Invalid language pack: {}", l),
+            ErrorKind::InvalidSound(s) => write!(f, "Invalid sound: {}", s),
+            ErrorKind::FileError(path, e) => write!(f, "{}: {}", path.display(), e),
+            ErrorKind::ConfigError(path, e) => write!(f, "Config {}: {}", path.display(), e),
+            ErrorKind::ThemeError(path, e) => write!(f, "Theme {}: {}", path.display(), e),
+            ErrorKind::LanguageError(path, e) => {
+                write!(f, "Language {}: {}", path.display(), e)
+            }
+            ErrorKind::SoundError(path, e) => write!(f, "Sound {}: {}", path.display(), e),
+            #[cfg(feature = "audio")]
+            ErrorKind::AudioError(e) => write!(f, "Audio output: {}", e),
+            ErrorKind::WordlistError(path, e) => write!(f, "Wordlist {}: {}", path.display(), e),
+            ErrorKind::HistoryError(path, line, e) => {
+                write!(f, "History {}:{}: {}", path.display(), line, e)
+            }
+            ErrorKind::DatabaseError(path, e) => write!(f, "Database {}: {}", path.display(), e),
+            ErrorKind::UnsupportedBackend(b) => {
+                write!(f, "History backend {} is not available in this build", b)
+            }
+            ErrorKind::MissingResult(d) => write!(f, "No saved result from {} was found", d),
+        }
+    }
+}
+
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::Error(e) => Some(e.as_ref()),
+            ErrorKind::IOError(e) | ErrorKind::FileError(_, e) => Some(e),
+            ErrorKind::ParseError(e) => Some(e),
+            ErrorKind::ParseFloatError(e) => Some(e),
+            ErrorKind::JsonError(e) => Some(e),
+            ErrorKind::TomlError(e) => Some(e),
+            ErrorKind::TomlSerializeError(e) => Some(e),
+            ErrorKind::CsvError(e) => Some(e),
+            #[cfg(feature = "sqlite")]
+            ErrorKind::SqliteError(e) => Some(e),
+            ErrorKind::ConfigError(_, e)
+            | ErrorKind::ThemeError(_, e)
+            | ErrorKind::LanguageError(_, e)
+            | ErrorKind::SoundError(_, e)
+            | ErrorKind::DatabaseError(_, e)
+            | ErrorKind::HistoryError(_, _, e) => Some(e.as_ref()),
+            _ => None,
         }
     }
 }
@@ -34,8 +130,39 @@ impl From<std::num::ParseFloatError> for ErrorKind {
     }
 }
 
+impl From<serde_json::Error> for ErrorKind {
+    fn from(e: serde_json::Error) -> Self {
+        ErrorKind::JsonError(e)
+    }
+}
+
+impl From<toml::de::Error> for ErrorKind {
+    fn from(e: toml::de::Error) -> Self {
+        ErrorKind::TomlError(e)
+    }
+}
+
+impl From<toml::ser::Error> for ErrorKind {
+    fn from(e: toml::ser::Error) -> Self {
+        ErrorKind::TomlSerializeError(e)
+    }
+}
+
+impl From<csv::Error> for ErrorKind {
+    fn from(e: csv::Error) -> Self {
+        ErrorKind::CsvError(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for ErrorKind {
+    fn from(e: rusqlite::Error) -> Self {
+        ErrorKind::SqliteError(e)
+    }
+}
+
 impl From<Box<dyn std::error::Error>> for ErrorKind {
     fn from(e: Box<dyn std::error::Error>) -> Self {
         ErrorKind::Error(e)
     }
-}
\ No newline at end of file
+}
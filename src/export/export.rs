@@ -0,0 +1,172 @@
+use std::fmt::Write;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::ErrorKind;
+use crate::serializers::wpm_results::WpmResult;
+use crate::stats::stats::{by_mode, by_week};
+
+/// Where the in-app export action writes its files.
+pub const EXPORT_DIR: &str = "resource/exports";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One row per result, for spreadsheets.
+    #[default]
+    Csv,
+    /// A pretty printed array of results.
+    Json,
+    /// A report with per mode and weekly summary tables, for wikis.
+    Markdown,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] =
+        [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Markdown];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "markdown",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" => Ok(ExportFormat::Markdown),
+            _ => ExportFormat::ALL
+                .iter()
+                .copied()
+                .find(|f| f.name() == s)
+                .ok_or_else(|| ErrorKind::InvalidArgument(format!("unknown export format {}", s))),
+        }
+    }
+}
+
+/// A CSV row. Every column is always written, unlike the optional fields of `WpmResult`.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    date_time: String,
+    mode: String,
+    wpm: f64,
+    awpm: f64,
+    accuracy: f64,
+    duration: f64,
+    language: &'a str,
+    layout: &'a str,
+    source: &'a str,
+}
+
+impl<'a> From<&'a WpmResult> for CsvRow<'a> {
+    fn from(result: &'a WpmResult) -> Self {
+        CsvRow {
+            date_time: result.date_time.to_rfc3339(),
+            mode: result.mode.map(|m| m.to_string()).unwrap_or_default(),
+            wpm: result.wpm,
+            awpm: result.awpm,
+            accuracy: result.accuracy,
+            duration: result.duration,
+            language: result.language.as_deref().unwrap_or_default(),
+            layout: result.layout.as_deref().unwrap_or_default(),
+            source: result.source.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+/// Renders `results` in `format`.
+pub fn export(results: &[WpmResult], format: ExportFormat) -> Result<String, ErrorKind> {
+    match format {
+        ExportFormat::Csv => to_csv(results),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(results)? + "\n"),
+        ExportFormat::Markdown => Ok(to_markdown(results)),
+    }
+}
+
+/// Writes an export to `path`, creating its directory if needed.
+pub fn write(path: impl AsRef<Path>, content: &str) -> Result<(), ErrorKind> {
+    let path = path.as_ref();
+    let file_error = |e| ErrorKind::FileError(path.to_path_buf(), e);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(file_error)?;
+    }
+    std::fs::write(path, content).map_err(file_error)
+}
+
+fn to_csv(results: &[WpmResult]) -> Result<String, ErrorKind> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for result in results {
+        writer.serialize(CsvRow::from(result))?;
+    }
+    let bytes = writer.into_inner().map_err(|e| ErrorKind::IOError(e.into_error()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn to_markdown(results: &[WpmResult]) -> String {
+    let mut report = String::from("# Typing history\n\n");
+
+    let (first, last) = match (
+        results.iter().map(|r| r.date_time).min(),
+        results.iter().map(|r| r.date_time).max(),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            report.push_str("No results.\n");
+            return report;
+        }
+    };
+    let _ = writeln!(
+        report,
+        "{} results from {} to {}.\n",
+        results.len(),
+        first.format("%Y-%m-%d"),
+        last.format("%Y-%m-%d")
+    );
+
+    report.push_str("## By mode\n\n");
+    report.push_str("| Mode | Tests | Best WPM | Average WPM | Average accuracy |\n");
+    report.push_str("| --- | ---: | ---: | ---: | ---: |\n");
+    for (mode, summary) in &by_mode(results) {
+        let _ = writeln!(
+            report,
+            "| {} | {} | {:.1} | {:.1} | {:.1}% |",
+            mode,
+            summary.tests,
+            summary.best_wpm,
+            summary.average_wpm(),
+            summary.average_accuracy() * 100.0
+        );
+    }
+
+    report.push_str("\n## Weekly trend\n\n");
+    report.push_str("| Week | Tests | Best WPM | Average WPM | Change |\n");
+    report.push_str("| --- | ---: | ---: | ---: | ---: |\n");
+    let mut previous: Option<f64> = None;
+    for ((year, week), summary) in &by_week(results) {
+        let average = summary.average_wpm();
+        let change = match previous {
+            Some(previous) => format!("{:+.1}", average - previous),
+            None => "-".to_string(),
+        };
+        let _ = writeln!(
+            report,
+            "| {}-W{:02} | {} | {:.1} | {:.1} | {} |",
+            year, week, summary.tests, summary.best_wpm, average, change
+        );
+        previous = Some(average);
+    }
+
+    report
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod export;
+#[allow(clippy::module_inception)]
+mod tests;
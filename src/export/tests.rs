@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use crate::engine::engine::TestMode;
+    use crate::export::export::{export, ExportFormat};
+    use crate::serializers::wpm_results::WpmResult;
+
+    fn results() -> Vec<WpmResult> {
+        [(4, 60.0, Some(TestMode::Time(30))), (5, 80.0, Some(TestMode::Time(30))), (12, 50.0, None)]
+            .iter()
+            .map(|(day, wpm, mode)| {
+                let mut result = WpmResult::new(*wpm, 0.9, 0.5, *wpm);
+                result.date_time = Local.with_ymd_and_hms(2024, 3, *day, 12, 0, 0).unwrap();
+                result.mode = *mode;
+                result
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let csv = export(&results(), ExportFormat::Csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "date_time,mode,wpm,awpm,accuracy,duration,language,layout,source");
+        assert!(lines[1].ends_with(",time 30,60.0,60.0,0.9,0.5,,,"));
+        assert!(lines[3].contains(",,50.0,"));
+    }
+
+    #[test]
+    fn test_export_json() {
+        let json = export(&results(), ExportFormat::Json).unwrap();
+        let parsed: Vec<WpmResult> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, results());
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let report = export(&results(), ExportFormat::Markdown).unwrap();
+
+        assert!(report.contains("3 results from 2024-03-04 to 2024-03-12."));
+        assert!(report.contains("| time 30 | 2 | 80.0 | 70.0 | 90.0% |"));
+        assert!(report.contains("| unknown | 1 | 50.0 | 50.0 | 90.0% |"));
+        assert!(report.contains("| 2024-W10 | 2 | 80.0 | 70.0 | - |"));
+        assert!(report.contains("| 2024-W11 | 1 | 50.0 | 50.0 | -20.0 |"));
+
+        assert!(export(&[], ExportFormat::Markdown).unwrap().contains("No results."));
+    }
+}
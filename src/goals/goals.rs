@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::error::ErrorKind;
+use crate::serializers::wpm_results::WpmResult;
+
+/// How much practice a day needs to count towards the goal, set with `daily_goal = "..."`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DailyGoal {
+    /// Minutes spent typing in finished tests.
+    Minutes(u64),
+    /// Finished tests.
+    Tests(u64),
+}
+
+impl DailyGoal {
+    pub const PRESETS: [DailyGoal; 8] = [
+        DailyGoal::Minutes(5),
+        DailyGoal::Minutes(10),
+        DailyGoal::Minutes(15),
+        DailyGoal::Minutes(30),
+        DailyGoal::Tests(3),
+        DailyGoal::Tests(5),
+        DailyGoal::Tests(10),
+        DailyGoal::Tests(20),
+    ];
+}
+
+impl Default for DailyGoal {
+    fn default() -> Self {
+        DailyGoal::Minutes(10)
+    }
+}
+
+impl std::fmt::Display for DailyGoal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DailyGoal::Minutes(minutes) => write!(f, "{} minutes", minutes),
+            DailyGoal::Tests(tests) => write!(f, "{} tests", tests),
+        }
+    }
+}
+
+impl std::str::FromStr for DailyGoal {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ErrorKind::InvalidGoal(s.to_string());
+        let (amount, unit) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let amount = amount.parse().map_err(|_| invalid())?;
+        match unit.trim() {
+            "minute" | "minutes" => Ok(DailyGoal::Minutes(amount)),
+            "test" | "tests" => Ok(DailyGoal::Tests(amount)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for DailyGoal {
+    type Error = ErrorKind;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DailyGoal> for String {
+    fn from(goal: DailyGoal) -> Self {
+        goal.to_string()
+    }
+}
+
+/// The practice done on one day.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Day {
+    pub tests: u64,
+    pub minutes: f64,
+}
+
+impl Day {
+    /// How busy the day was from 0 (no practice) to 4, for the heatmap.
+    pub fn level(&self) -> usize {
+        match self.tests {
+            0 => 0,
+            1..=2 => 1,
+            3..=5 => 2,
+            6..=9 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Something worth celebrating that a result achieved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Milestone {
+    /// Faster than every earlier result.
+    PersonalBest { wpm: f64, previous: f64 },
+    /// The first result reaching one of the configured WPM thresholds.
+    Threshold(u64),
+    /// The daily goal was met with this result.
+    GoalReached(DailyGoal),
+}
+
+impl std::fmt::Display for Milestone {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Milestone::PersonalBest { wpm, previous } => {
+                write!(f, "New personal best: {:.0} wpm (was {:.0})", wpm, previous)
+            }
+            Milestone::Threshold(wpm) => write!(f, "First test over {} wpm", wpm),
+            Milestone::GoalReached(goal) => write!(f, "Daily goal of {} reached", goal),
+        }
+    }
+}
+
+/// Practice per local day, built from the history and kept up to date as tests finish.
+#[derive(Debug, Clone, Default)]
+pub struct Practice {
+    days: BTreeMap<NaiveDate, Day>,
+    best_wpm: Option<f64>,
+}
+
+impl Practice {
+    pub fn new(results: &[WpmResult]) -> Self {
+        let mut practice = Practice::default();
+        for result in results {
+            practice.add(result);
+        }
+        practice
+    }
+
+    fn add(&mut self, result: &WpmResult) {
+        let day = self.days.entry(result.date_time.date_naive()).or_default();
+        day.tests += 1;
+        day.minutes += result.duration;
+        if result.is_valid() {
+            self.best_wpm = Some(self.best_wpm.map_or(result.wpm, |best| best.max(result.wpm)));
+        }
+    }
+
+    /// Adds a newly finished result, returning the milestones it reached. Flagged results
+    /// count as practice but reach no speed milestones.
+    pub fn record(
+        &mut self,
+        result: &WpmResult,
+        goal: DailyGoal,
+        thresholds: &[u64],
+    ) -> Vec<Milestone> {
+        let date = result.date_time.date_naive();
+        let goal_met = self.goal_progress(goal, date) >= 1.0;
+        let previous = self.best_wpm;
+        self.add(result);
+
+        let mut milestones = Vec::new();
+        if result.is_valid() {
+            if let Some(previous) = previous.filter(|previous| result.wpm > *previous) {
+                milestones.push(Milestone::PersonalBest { wpm: result.wpm, previous });
+            }
+            let best = previous.unwrap_or(0.0);
+            milestones.extend(
+                thresholds
+                    .iter()
+                    .filter(|t| best < **t as f64 && result.wpm >= **t as f64)
+                    .max()
+                    .map(|t| Milestone::Threshold(*t)),
+            );
+        }
+        if !goal_met && self.goal_progress(goal, date) >= 1.0 {
+            milestones.push(Milestone::GoalReached(goal));
+        }
+        milestones
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Day {
+        self.days.get(&date).copied().unwrap_or_default()
+    }
+
+    pub fn best_wpm(&self) -> Option<f64> {
+        self.best_wpm
+    }
+
+    /// How much of `goal` was done on `date`, from 0 to 1.
+    pub fn goal_progress(&self, goal: DailyGoal, date: NaiveDate) -> f64 {
+        let day = self.day(date);
+        let progress = match goal {
+            DailyGoal::Minutes(minutes) => day.minutes / minutes.max(1) as f64,
+            DailyGoal::Tests(tests) => day.tests as f64 / tests.max(1) as f64,
+        };
+        progress.min(1.0)
+    }
+
+    /// Days in a row with practice, up to `today`. A streak is not broken before the end
+    /// of the day, so it may end yesterday.
+    pub fn streak(&self, today: NaiveDate) -> u32 {
+        let mut date = match self.days.contains_key(&today) {
+            true => today,
+            false => today - Duration::days(1),
+        };
+        let mut streak = 0;
+        while self.days.contains_key(&date) {
+            streak += 1;
+            date -= Duration::days(1);
+        }
+        streak
+    }
+
+    /// The last `count` weeks up to the one holding `today`, oldest first, each from
+    /// Monday to Sunday. Days after `today` are `None`.
+    pub fn weeks(&self, today: NaiveDate, count: usize) -> Vec<[Option<Day>; 7]> {
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        (0..count)
+            .rev()
+            .map(|week| {
+                let start = monday - Duration::weeks(week as i64);
+                let mut days = [None; 7];
+                for (i, day) in days.iter_mut().enumerate() {
+                    let date = start + Duration::days(i as i64);
+                    if date <= today {
+                        *day = Some(self.day(date));
+                    }
+                }
+                days
+            })
+            .collect()
+    }
+}
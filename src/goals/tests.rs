@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, TimeZone};
+    use crate::goals::goals::*;
+    use crate::serializers::wpm_results::WpmResult;
+
+    fn result(day: u32, wpm: f64) -> WpmResult {
+        let mut result = WpmResult::new(wpm, 1.0, 0.5, wpm);
+        result.date_time = Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        result
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_daily_goal_parse() {
+        assert_eq!("10 minutes".parse::<DailyGoal>().unwrap(), DailyGoal::Minutes(10));
+        assert_eq!("1 test".parse::<DailyGoal>().unwrap(), DailyGoal::Tests(1));
+        assert_eq!(DailyGoal::Tests(5).to_string(), "5 tests");
+        assert!("ten minutes".parse::<DailyGoal>().is_err());
+        assert!("10 hours".parse::<DailyGoal>().is_err());
+    }
+
+    #[test]
+    fn test_streak_and_goal() {
+        let practice = Practice::new(&[
+            result(1, 50.0),
+            result(3, 50.0),
+            result(4, 50.0),
+            result(4, 60.0),
+        ]);
+
+        assert_eq!(practice.streak(date(4)), 2);
+        // Today's practice is still to come
+        assert_eq!(practice.streak(date(5)), 2);
+        assert_eq!(practice.streak(date(6)), 0);
+
+        assert_eq!(practice.day(date(4)).tests, 2);
+        assert_eq!(practice.goal_progress(DailyGoal::Tests(4), date(4)), 0.5);
+        assert_eq!(practice.goal_progress(DailyGoal::Minutes(1), date(4)), 1.0);
+        assert_eq!(practice.best_wpm(), Some(60.0));
+    }
+
+    #[test]
+    fn test_weeks() {
+        let practice = Practice::new(&[result(4, 50.0), result(6, 50.0)]);
+        // 2024-03-06 is a Wednesday
+        let weeks = practice.weeks(date(6), 2);
+
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1][0].unwrap().tests, 1);
+        assert_eq!(weeks[1][2].unwrap().level(), 1);
+        assert_eq!(weeks[1][3], None);
+        assert_eq!(weeks[0][6].unwrap().tests, 0);
+    }
+
+    #[test]
+    fn test_milestones() {
+        let mut practice = Practice::new(&[result(1, 55.0)]);
+        let goal = DailyGoal::Tests(2);
+        let thresholds = [40, 60, 80];
+
+        assert_eq!(practice.record(&result(2, 50.0), goal, &thresholds), vec![]);
+        assert_eq!(
+            practice.record(&result(2, 65.0), goal, &thresholds),
+            vec![
+                Milestone::PersonalBest { wpm: 65.0, previous: 55.0 },
+                Milestone::Threshold(60),
+                Milestone::GoalReached(goal),
+            ]
+        );
+        // The goal is only reached once a day
+        assert_eq!(practice.record(&result(2, 30.0), goal, &thresholds), vec![]);
+
+        // A first result is no personal best, but may cross thresholds
+        let mut practice = Practice::default();
+        assert_eq!(
+            practice.record(&result(1, 45.0), goal, &thresholds),
+            vec![Milestone::Threshold(40)]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, TimeZone};
+use crate::engine::engine::{Keystroke, TestMode};
+use crate::history::history::Query;
+use crate::serializers::wpm_results::WpmResult;
+use crate::validation::validation::reconstruct;
+
+/// Columns of the history table, each of which it can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Column {
+    #[default]
+    Date,
+    Mode,
+    Wpm,
+    Accuracy,
+    Duration,
+    Language,
+}
+
+impl Column {
+    pub const ALL: [Column; 6] = [
+        Column::Date,
+        Column::Mode,
+        Column::Wpm,
+        Column::Accuracy,
+        Column::Duration,
+        Column::Language,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Date => "Date",
+            Column::Mode => "Mode",
+            Column::Wpm => "WPM",
+            Column::Accuracy => "Accuracy",
+            Column::Duration => "Duration",
+            Column::Language => "Language",
+        }
+    }
+
+    pub fn next(&self) -> Column {
+        let index = Column::ALL.iter().position(|c| c == self).unwrap_or(0);
+        Column::ALL[(index + 1) % Column::ALL.len()]
+    }
+
+    /// Results missing the value sort before those that have it.
+    fn compare(&self, a: &WpmResult, b: &WpmResult) -> Ordering {
+        match self {
+            Column::Date => a.date_time.cmp(&b.date_time),
+            Column::Mode => mode_key(a.mode).cmp(&mode_key(b.mode)),
+            Column::Wpm => a.wpm.total_cmp(&b.wpm),
+            Column::Accuracy => a.accuracy.total_cmp(&b.accuracy),
+            Column::Duration => a.duration.total_cmp(&b.duration),
+            Column::Language => a.language.cmp(&b.language),
+        }
+    }
+}
+
+/// Time tests before word tests, each shortest first.
+fn mode_key(mode: Option<TestMode>) -> Option<(u8, u64)> {
+    mode.map(|mode| match mode {
+        TestMode::Time(secs) => (0, secs),
+        TestMode::Words(count) => (1, count as u64),
+    })
+}
+
+/// How far back the history table goes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DateRange {
+    #[default]
+    All,
+    Today,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateRange {
+    pub const ALL: [DateRange; 5] = [
+        DateRange::All,
+        DateRange::Today,
+        DateRange::Week,
+        DateRange::Month,
+        DateRange::Year,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateRange::All => "all time",
+            DateRange::Today => "today",
+            DateRange::Week => "last 7 days",
+            DateRange::Month => "last 30 days",
+            DateRange::Year => "last year",
+        }
+    }
+
+    pub fn next(&self) -> DateRange {
+        let index = DateRange::ALL.iter().position(|r| r == self).unwrap_or(0);
+        DateRange::ALL[(index + 1) % DateRange::ALL.len()]
+    }
+
+    /// When the range starts as seen at `now`, `None` for all time.
+    pub fn start(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let days = match self {
+            DateRange::All => return None,
+            DateRange::Today => {
+                let midnight = now.date_naive().and_hms_opt(0, 0, 0)?;
+                return Local.from_local_datetime(&midnight).earliest();
+            }
+            DateRange::Week => 7,
+            DateRange::Month => 30,
+            DateRange::Year => 365,
+        };
+        Some(now - chrono::Duration::days(days))
+    }
+}
+
+/// Which results the history table shows.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    /// Looked for in the date, mode, language, source and tags, ignoring case.
+    pub text: String,
+    pub mode: Option<TestMode>,
+    pub range: DateRange,
+}
+
+impl HistoryFilter {
+    pub fn query(&self, now: DateTime<Local>) -> Query {
+        Query {
+            from: self.range.start(now),
+            mode: self.mode,
+            ..Query::default()
+        }
+    }
+
+    pub fn matches(&self, result: &WpmResult, now: DateTime<Local>) -> bool {
+        self.query(now).matches(result) && matches_text(result, &self.text)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == HistoryFilter::default()
+    }
+
+    /// What the filter keeps, e.g. `"time 30, last 7 days, /warmup"`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(mode) = self.mode {
+            parts.push(mode.to_string());
+        }
+        if self.range != DateRange::All {
+            parts.push(self.range.label().to_string());
+        }
+        if !self.text.is_empty() {
+            parts.push(format!("/{}", self.text));
+        }
+        parts.join(", ")
+    }
+}
+
+fn matches_text(result: &WpmResult, text: &str) -> bool {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return true;
+    }
+    let mut fields = vec![result.date_time.format("%Y-%m-%d %H:%M").to_string()];
+    fields.extend(result.mode.map(|m| m.to_string()));
+    fields.extend(result.language.clone());
+    fields.extend(result.source.clone());
+    fields.extend(result.tags.iter().cloned());
+    fields.iter().any(|field| field.to_lowercase().contains(&text))
+}
+
+/// The mode after `current` among those of `results`, cycling back to every mode.
+pub fn next_mode(current: Option<TestMode>, results: &[WpmResult]) -> Option<TestMode> {
+    let mut modes = results.iter().filter_map(|r| r.mode).collect::<Vec<_>>();
+    modes.sort_by_key(|m| mode_key(Some(*m)));
+    modes.dedup();
+    match current.and_then(|mode| modes.iter().position(|m| *m == mode)) {
+        Some(index) => modes.get(index + 1).copied(),
+        None => modes.first().copied(),
+    }
+}
+
+/// How the history table is sorted and filtered, and the rows that leaves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryView {
+    pub column: Column,
+    pub descending: bool,
+    pub filter: HistoryFilter,
+    /// Indices of the shown results, in the order shown.
+    pub rows: Vec<usize>,
+}
+
+impl HistoryView {
+    /// Newest first, the way a history is usually read.
+    pub fn new() -> Self {
+        HistoryView {
+            descending: true,
+            ..HistoryView::default()
+        }
+    }
+
+    /// Works out the rows again from `results` as they are at `now`. Results that compare
+    /// equal stay in date order.
+    pub fn refresh(&mut self, results: &[WpmResult], now: DateTime<Local>) {
+        let mut rows = (0..results.len())
+            .filter(|i| self.filter.matches(&results[*i], now))
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| {
+            let order = self.column.compare(&results[*a], &results[*b]);
+            match self.descending {
+                true => order.reverse(),
+                false => order,
+            }
+        });
+        self.rows = rows;
+    }
+}
+
+/// Tags as typed, separated by commas.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::<String>::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// A saved test typed out again at the pace it was typed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    pub keystrokes: Vec<Keystroke>,
+    pub started: Instant,
+}
+
+impl Playback {
+    pub fn new(keystrokes: Vec<Keystroke>, started: Instant) -> Self {
+        Playback { keystrokes, started }
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    /// The text typed so far at `now`.
+    pub fn typed_at(&self, now: Instant) -> String {
+        let elapsed = self.elapsed(now);
+        let played = self.keystrokes.iter().take_while(|k| k.at <= elapsed).count();
+        reconstruct(&self.keystrokes[..played])
+    }
+
+    pub fn is_done_at(&self, now: Instant) -> bool {
+        self.keystrokes.last().is_none_or(|k| k.at <= self.elapsed(now))
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::engine::engine::{KeyKind, Keystroke, TestMode};
+use crate::error::ErrorKind;
+use crate::history::json_store::JsonStore;
+use crate::serializers::wpm_results::WpmResult;
+
+/// Where finished tests are kept, picked with `history = "..."` in the config.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryBackend {
+    /// One JSON result per line, read into memory on startup.
+    #[default]
+    Json,
+    /// An embedded SQLite database, needs the `sqlite` cargo feature.
+    Sqlite,
+}
+
+impl HistoryBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryBackend::Json => "json",
+            HistoryBackend::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Filters for `Store::query`. Fields left unset match every result.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Inclusive start of the date range.
+    pub from: Option<DateTime<Local>>,
+    /// Exclusive end of the date range.
+    pub to: Option<DateTime<Local>>,
+    pub mode: Option<TestMode>,
+    pub language: Option<String>,
+    pub layout: Option<String>,
+    /// Keeps only this many results with the highest WPM, best first.
+    pub top: Option<usize>,
+}
+
+impl Query {
+    pub fn matches(&self, result: &WpmResult) -> bool {
+        self.from.is_none_or(|from| result.date_time >= from)
+            && self.to.is_none_or(|to| result.date_time < to)
+            && (self.mode.is_none() || result.mode == self.mode)
+            && (self.language.is_none() || result.language == self.language)
+            && (self.layout.is_none() || result.layout == self.layout)
+    }
+
+    /// Applies `top` to matching results that are in date order.
+    pub fn limit(&self, mut results: Vec<WpmResult>) -> Vec<WpmResult> {
+        if let Some(top) = self.top {
+            results.sort_by(|a, b| b.wpm.total_cmp(&a.wpm));
+            results.truncate(top);
+        }
+        results
+    }
+}
+
+/// How one key was typed over every saved replay.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct KeyStat {
+    pub hits: u64,
+    pub misses: u64,
+    /// Time since the previous keystroke, summed over every press of the key.
+    pub total_time: Duration,
+}
+
+impl KeyStat {
+    pub fn presses(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        match self.presses() {
+            0 => 0.0,
+            presses => self.hits as f64 / presses as f64,
+        }
+    }
+
+    pub fn mean_time(&self) -> Duration {
+        match self.presses() {
+            0 => Duration::ZERO,
+            presses => self.total_time / presses as u32,
+        }
+    }
+}
+
+pub type KeyStats = BTreeMap<char, KeyStat>;
+
+/// Counts the character keystrokes of `replay` into `stats`. Backspaces are left out, but
+/// still end the time measured for the following key.
+pub fn add_key_stats(stats: &mut KeyStats, replay: &[Keystroke]) {
+    let mut previous = None;
+    for keystroke in replay {
+        if let KeyKind::Char(c) = keystroke.key {
+            let stat = stats.entry(c).or_default();
+            match keystroke.correct {
+                true => stat.hits += 1,
+                false => stat.misses += 1,
+            }
+            if let Some(previous) = previous {
+                stat.total_time += keystroke.at.saturating_sub(previous);
+            }
+        }
+        previous = Some(keystroke.at);
+    }
+}
+
+/// Saved typing test results. Results are told apart by the moment they were taken.
+pub trait Store {
+    /// Saves a finished test along with the keystrokes that produced it.
+    fn add(&mut self, result: &WpmResult, replay: &[Keystroke]) -> Result<(), ErrorKind>;
+
+    /// Saves results that come without keystrokes, such as imported ones.
+    fn add_all(&mut self, results: &[WpmResult]) -> Result<(), ErrorKind> {
+        results.iter().try_for_each(|result| self.add(result, &[]))
+    }
+
+    /// Saved results matching `query`, oldest first, or best first when `top` is set.
+    fn query(&self, query: &Query) -> Result<Vec<WpmResult>, ErrorKind>;
+
+    /// The keystrokes of the result taken at `date_time`, empty when none were saved.
+    fn replay(&self, date_time: &DateTime<Local>) -> Result<Vec<Keystroke>, ErrorKind>;
+
+    /// Replaces the tags of the result taken at `date_time`. Returns false when there is no
+    /// such result.
+    fn set_tags(&mut self, date_time: &DateTime<Local>, tags: &[String]) -> Result<bool, ErrorKind>;
+
+    /// Removes the result taken at `date_time` with its keystrokes, taking them out of the
+    /// per key stats. Returns false when there is no such result.
+    fn delete(&mut self, date_time: &DateTime<Local>) -> Result<bool, ErrorKind>;
+
+    /// Per key stats over every saved replay.
+    fn key_stats(&self) -> Result<KeyStats, ErrorKind>;
+}
+
+/// Opens the history kept by `backend`. `path` is the JSON-lines history; the SQLite
+/// database sits next to it with a `db` extension and imports it the first time it is
+/// opened. Problems that still leave a usable store, like unreadable lines, are returned
+/// along with it.
+pub fn open(
+    backend: HistoryBackend,
+    path: impl AsRef<Path>,
+) -> Result<(Box<dyn Store>, Vec<ErrorKind>), ErrorKind> {
+    let path = path.as_ref();
+    match backend {
+        HistoryBackend::Json => {
+            let (store, errors) = JsonStore::open(path);
+            Ok((Box::new(store), errors))
+        }
+        #[cfg(feature = "sqlite")]
+        HistoryBackend::Sqlite => {
+            use crate::history::sqlite_store::SqliteStore;
+            let mut store = SqliteStore::open(path.with_extension("db"))?;
+            let errors = store.migrate_from_json(path)?;
+            Ok((Box::new(store), errors))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        HistoryBackend::Sqlite => Err(ErrorKind::UnsupportedBackend(backend.name().to_string())),
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::engine::engine::Keystroke;
+use crate::error::ErrorKind;
+use crate::history::history::{add_key_stats, KeyStats, Query, Store};
+use crate::serializers::wpm_results::{read_json_lines_raw, WpmResult};
+use crate::validation::validation::verify;
+
+/// A line of the history file. The replay sits next to the result fields, so older
+/// versions still read the line as a plain `WpmResult`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    #[serde(flatten)]
+    pub result: WpmResult,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replay: Vec<Keystroke>,
+}
+
+/// The JSON-lines history, kept in memory and appended to as tests finish.
+pub struct JsonStore {
+    path: PathBuf,
+    records: Vec<Record>,
+    /// Lines of the file that could not be read, kept to be written back untouched.
+    unreadable: Vec<String>,
+    /// Whether the file could be read at all. One that couldn't is never written over.
+    readable: bool,
+}
+
+impl JsonStore {
+    /// Reads the history at `path`, skipping lines it cannot read. A missing file is an
+    /// empty history and an unreadable one is reported and treated the same. Results that
+    /// fail verification are flagged.
+    pub fn open(path: impl AsRef<Path>) -> (Self, Vec<ErrorKind>) {
+        let path = path.as_ref().to_path_buf();
+        let (mut records, failed, readable) = match read_json_lines_raw::<Record>(&path) {
+            Ok((records, failed)) => (records, failed, true),
+            Err(e) => (Vec::new(), vec![(String::new(), e)], false),
+        };
+        for record in &mut records {
+            verify(&mut record.result, &record.replay);
+        }
+        let (mut unreadable, errors): (Vec<_>, _) = failed.into_iter().unzip();
+        unreadable.retain(|line| !line.is_empty());
+        (JsonStore { path, records, unreadable, readable }, errors)
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Writes every record over the file, through a temporary file so a failed write leaves
+    /// the old history in place. Lines that could not be read go after them as they were.
+    fn rewrite(&self) -> Result<(), ErrorKind> {
+        if !self.readable {
+            let reason = "could not be read when opened, so it is left as it is";
+            return Err(ErrorKind::FileError(self.path.clone(), std::io::Error::other(reason)));
+        }
+        let temp = self.path.with_extension("tmp");
+        let mut lines = String::new();
+        for record in &self.records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        for line in &self.unreadable {
+            lines.push_str(line);
+            lines.push('\n');
+        }
+        std::fs::write(&temp, lines)
+            .and_then(|()| std::fs::rename(&temp, &self.path))
+            .map_err(|e| ErrorKind::FileError(self.path.clone(), e))
+    }
+
+    fn position(&self, date_time: &DateTime<Local>) -> Option<usize> {
+        self.records.iter().position(|r| r.result.date_time == *date_time)
+    }
+}
+
+impl Store for JsonStore {
+    fn add(&mut self, result: &WpmResult, replay: &[Keystroke]) -> Result<(), ErrorKind> {
+        let record = Record {
+            result: result.clone(),
+            replay: replay.to_vec(),
+        };
+        let file_error = |e| ErrorKind::FileError(self.path.clone(), e);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(file_error)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?).map_err(file_error)?;
+        self.records.push(record);
+        Ok(())
+    }
+
+    fn query(&self, query: &Query) -> Result<Vec<WpmResult>, ErrorKind> {
+        let mut results = self
+            .records
+            .iter()
+            .filter(|r| query.matches(&r.result))
+            .map(|r| r.result.clone())
+            .collect::<Vec<_>>();
+        results.sort_by_key(|r| r.date_time);
+        Ok(query.limit(results))
+    }
+
+    fn replay(&self, date_time: &DateTime<Local>) -> Result<Vec<Keystroke>, ErrorKind> {
+        Ok(self
+            .records
+            .iter()
+            .find(|r| r.result.date_time == *date_time)
+            .map(|r| r.replay.clone())
+            .unwrap_or_default())
+    }
+
+    fn set_tags(
+        &mut self,
+        date_time: &DateTime<Local>,
+        tags: &[String],
+    ) -> Result<bool, ErrorKind> {
+        let index = match self.position(date_time) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let previous = std::mem::replace(&mut self.records[index].result.tags, tags.to_vec());
+        if let Err(e) = self.rewrite() {
+            self.records[index].result.tags = previous;
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    fn delete(&mut self, date_time: &DateTime<Local>) -> Result<bool, ErrorKind> {
+        let index = match self.position(date_time) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let record = self.records.remove(index);
+        if let Err(e) = self.rewrite() {
+            self.records.insert(index, record);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    fn key_stats(&self) -> Result<KeyStats, ErrorKind> {
+        let mut stats = KeyStats::new();
+        for record in &self.records {
+            add_key_stats(&mut stats, &record.replay);
+        }
+        Ok(stats)
+    }
+}
@@ -0,0 +1,8 @@
+pub mod browser;
+#[allow(clippy::module_inception)]
+pub mod history;
+pub mod json_store;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+#[allow(clippy::module_inception)]
+mod tests;
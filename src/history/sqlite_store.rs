@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use rusqlite::types::{ToSql, Type};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use crate::engine::engine::{KeyKind, Keystroke, Sample};
+use crate::error::ErrorKind;
+use crate::history::history::{add_key_stats, KeyStat, KeyStats, Query, Store};
+use crate::history::json_store::Record;
+use crate::serializers::wpm_results::{read_json_lines, WpmResult};
+use crate::validation::validation::verify;
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS results (
+        id INTEGER PRIMARY KEY,
+        timestamp INTEGER NOT NULL,
+        date_time TEXT NOT NULL,
+        wpm REAL NOT NULL,
+        accuracy REAL NOT NULL,
+        duration REAL NOT NULL,
+        awpm REAL NOT NULL,
+        mode TEXT,
+        language TEXT,
+        layout TEXT,
+        source TEXT,
+        flags TEXT,
+        integrity TEXT,
+        tags TEXT,
+        difficulty TEXT
+    );
+    CREATE TABLE IF NOT EXISTS replays (
+        result_id INTEGER NOT NULL REFERENCES results (id) ON DELETE CASCADE,
+        seq INTEGER NOT NULL,
+        at_ms INTEGER NOT NULL,
+        key TEXT,
+        correct INTEGER NOT NULL,
+        blocked INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (result_id, seq)
+    );
+    CREATE TABLE IF NOT EXISTS samples (
+        result_id INTEGER NOT NULL REFERENCES results (id) ON DELETE CASCADE,
+        second REAL NOT NULL,
+        wpm REAL NOT NULL,
+        raw REAL NOT NULL,
+        errors INTEGER NOT NULL,
+        PRIMARY KEY (result_id, second)
+    );
+    CREATE TABLE IF NOT EXISTS key_stats (
+        key TEXT PRIMARY KEY,
+        hits INTEGER NOT NULL,
+        misses INTEGER NOT NULL,
+        total_ms INTEGER NOT NULL
+    );
+";
+
+/// Columns added after the first release, created on databases that predate them.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("results", "source", "TEXT"),
+    ("results", "flags", "TEXT"),
+    ("results", "integrity", "TEXT"),
+    ("results", "tags", "TEXT"),
+    ("results", "difficulty", "TEXT"),
+    ("replays", "blocked", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+/// Makes `timestamp` the key results are told apart by, also on databases created when
+/// `date_time` was. A `date_time` written with another offset is still the same moment.
+const UNIQUE_TIMESTAMP: &str = "
+    DROP INDEX IF EXISTS results_timestamp;
+    CREATE UNIQUE INDEX results_timestamp ON results (timestamp);
+";
+
+/// Bumped once every line of the JSON-lines history has been imported, so it only happens once.
+const MIGRATED_VERSION: i64 = 1;
+
+/// The history in an SQLite database. Per key stats are kept up to date as results are
+/// added instead of being worked out from the replays.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ErrorKind> {
+        let path = path.as_ref();
+        let db_error =
+            |e: rusqlite::Error| ErrorKind::DatabaseError(path.to_path_buf(), Box::new(e.into()));
+        let conn = Connection::open(path).map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        add_columns(&conn).map_err(db_error)?;
+        let mut store = SqliteStore { conn };
+        store.unique_timestamps().map_err(|e| match e {
+            ErrorKind::SqliteError(e) => db_error(e),
+            e => e,
+        })?;
+        Ok(store)
+    }
+
+    /// Keeps the first of any results taken at the same moment, then indexes `timestamp` as
+    /// unique.
+    fn unique_timestamps(&mut self) -> Result<(), ErrorKind> {
+        let unique = self
+            .conn
+            .prepare("SELECT 1 FROM pragma_index_list('results') WHERE name = ? AND \"unique\"")?
+            .exists(["results_timestamp"])?;
+        if unique {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        let duplicates = tx
+            .prepare(
+                "SELECT id FROM results
+                    WHERE id NOT IN (SELECT MIN(id) FROM results GROUP BY timestamp)",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        for id in duplicates {
+            remove(&tx, id)?;
+        }
+        tx.execute_batch(UNIQUE_TIMESTAMP)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Imports the JSON-lines history at `path` unless that was done before, returning an
+    /// error for each line that could not be read. Results already in the database are kept,
+    /// so while some lines cannot be read the import is tried again on every open.
+    pub fn migrate_from_json(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<ErrorKind>, ErrorKind> {
+        let version: i64 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version >= MIGRATED_VERSION {
+            return Ok(Vec::new());
+        }
+
+        let (records, errors) = read_json_lines::<Record>(path)?;
+        let tx = self.conn.transaction()?;
+        for record in &records {
+            insert(&tx, &record.result, &record.replay)?;
+        }
+        if errors.is_empty() {
+            tx.pragma_update(None, "user_version", MIGRATED_VERSION)?;
+        }
+        tx.commit()?;
+        Ok(errors)
+    }
+}
+
+fn add_columns(conn: &Connection) -> rusqlite::Result<()> {
+    for (table, column, kind) in ADDED_COLUMNS {
+        let exists = conn
+            .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?", table))?
+            .exists([column])?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, kind))?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds a result unless one taken at the same moment is already saved.
+fn insert(conn: &Connection, result: &WpmResult, replay: &[Keystroke]) -> Result<(), ErrorKind> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO results
+            (timestamp, date_time, wpm, accuracy, duration, awpm, mode, language, layout, source,
+                flags, integrity, tags, difficulty)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            result.date_time.timestamp_micros(),
+            result.date_time.to_rfc3339(),
+            result.wpm,
+            result.accuracy,
+            result.duration,
+            result.awpm,
+            result.mode.map(|m| m.to_string()),
+            result.language,
+            result.layout,
+            result.source,
+            json_list(&result.flags)?,
+            result.integrity,
+            json_list(&result.tags)?,
+            match result.difficulty.is_default() {
+                true => None,
+                false => Some(serde_json::to_string(&result.difficulty)?),
+            },
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    let id = conn.last_insert_rowid();
+    let mut insert_key = conn.prepare_cached(
+        "INSERT INTO replays (result_id, seq, at_ms, key, correct, blocked)
+            VALUES (?, ?, ?, ?, ?, ?)",
+    )?;
+    for (seq, keystroke) in replay.iter().enumerate() {
+        let key = match keystroke.key {
+            KeyKind::Char(c) => Some(c.to_string()),
+            KeyKind::Backspace => None,
+        };
+        insert_key.execute(params![
+            id,
+            seq,
+            keystroke.at.as_millis() as i64,
+            key,
+            keystroke.correct,
+            keystroke.blocked
+        ])?;
+    }
+
+    let mut insert_sample = conn.prepare_cached(
+        "INSERT INTO samples (result_id, second, wpm, raw, errors) VALUES (?, ?, ?, ?, ?)",
+    )?;
+    for sample in &result.timeline {
+        insert_sample.execute(params![id, sample.second, sample.wpm, sample.raw, sample.errors])?;
+    }
+
+    let mut stats = KeyStats::new();
+    add_key_stats(&mut stats, replay);
+    let mut update_stat = conn.prepare_cached(
+        "INSERT INTO key_stats (key, hits, misses, total_ms) VALUES (?, ?, ?, ?)
+            ON CONFLICT (key) DO UPDATE SET
+                hits = hits + excluded.hits,
+                misses = misses + excluded.misses,
+                total_ms = total_ms + excluded.total_ms",
+    )?;
+    for (key, stat) in stats {
+        update_stat.execute(params![
+            key.to_string(),
+            stat.hits,
+            stat.misses,
+            stat.total_time.as_millis() as i64
+        ])?;
+    }
+    Ok(())
+}
+
+/// A list kept as a JSON array, `NULL` when empty.
+fn json_list<T: serde::Serialize>(list: &[T]) -> Result<Option<String>, ErrorKind> {
+    match list.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(serde_json::to_string(list)?)),
+    }
+}
+
+fn conversion_error(column: usize, e: impl ToString) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, Type::Text, e.to_string().into())
+}
+
+fn result_from_row(row: &Row) -> rusqlite::Result<WpmResult> {
+    let date_time: String = row.get(0)?;
+    let mode: Option<String> = row.get(5)?;
+    let flags: Option<String> = row.get(10)?;
+    let tags: Option<String> = row.get(12)?;
+    let difficulty: Option<String> = row.get(13)?;
+    Ok(WpmResult {
+        date_time: DateTime::parse_from_rfc3339(&date_time)
+            .map_err(|e| conversion_error(0, e))?
+            .with_timezone(&Local),
+        wpm: row.get(1)?,
+        accuracy: row.get(2)?,
+        duration: row.get(3)?,
+        awpm: row.get(4)?,
+        mode: mode
+            .map(|m| m.parse())
+            .transpose()
+            .map_err(|e| conversion_error(5, e))?,
+        language: row.get(6)?,
+        layout: row.get(7)?,
+        source: row.get(8)?,
+        timeline: Vec::new(),
+        flags: flags
+            .map(|f| serde_json::from_str(&f))
+            .transpose()
+            .map_err(|e| conversion_error(10, e))?
+            .unwrap_or_default(),
+        integrity: row.get(11)?,
+        tags: tags
+            .map(|t| serde_json::from_str(&t))
+            .transpose()
+            .map_err(|e| conversion_error(12, e))?
+            .unwrap_or_default(),
+        difficulty: difficulty
+            .map(|d| serde_json::from_str(&d))
+            .transpose()
+            .map_err(|e| conversion_error(13, e))?
+            .unwrap_or_default(),
+    })
+}
+
+fn sample_from_row(row: &Row) -> rusqlite::Result<Sample> {
+    Ok(Sample {
+        second: row.get(0)?,
+        wpm: row.get(1)?,
+        raw: row.get(2)?,
+        errors: row.get(3)?,
+    })
+}
+
+fn keystroke_from_row(row: &Row) -> rusqlite::Result<Keystroke> {
+    let key: Option<String> = row.get(1)?;
+    Ok(Keystroke {
+        at: Duration::from_millis(row.get(0)?),
+        key: match key {
+            Some(key) => KeyKind::Char(single_char(1, key)?),
+            None => KeyKind::Backspace,
+        },
+        correct: row.get(2)?,
+        blocked: row.get(3)?,
+    })
+}
+
+fn replay_of(conn: &Connection, result_id: i64) -> rusqlite::Result<Vec<Keystroke>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT at_ms, key, correct, blocked FROM replays WHERE result_id = ? ORDER BY seq",
+    )?;
+    let replay = stmt.query_map([result_id], keystroke_from_row)?.collect();
+    replay
+}
+
+/// Deletes a result with its replay and samples, taking its keystrokes off the key stats.
+fn remove(conn: &Connection, result_id: i64) -> Result<(), ErrorKind> {
+    let mut stats = KeyStats::new();
+    add_key_stats(&mut stats, &replay_of(conn, result_id)?);
+
+    // Replays and samples go with the result
+    conn.execute("DELETE FROM results WHERE id = ?", [result_id])?;
+    for (key, stat) in stats {
+        conn.execute(
+            "UPDATE key_stats SET
+                hits = hits - ?, misses = misses - ?, total_ms = total_ms - ?
+                WHERE key = ?",
+            params![
+                stat.hits,
+                stat.misses,
+                stat.total_time.as_millis() as i64,
+                key.to_string()
+            ],
+        )?;
+    }
+    conn.execute("DELETE FROM key_stats WHERE hits <= 0 AND misses <= 0", [])?;
+    Ok(())
+}
+
+fn single_char(column: usize, key: String) -> rusqlite::Result<char> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(conversion_error(column, format!("not a single key: {:?}", key))),
+    }
+}
+
+impl Store for SqliteStore {
+    fn add(&mut self, result: &WpmResult, replay: &[Keystroke]) -> Result<(), ErrorKind> {
+        let tx = self.conn.transaction()?;
+        insert(&tx, result, replay)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn add_all(&mut self, results: &[WpmResult]) -> Result<(), ErrorKind> {
+        let tx = self.conn.transaction()?;
+        for result in results {
+            insert(&tx, result, &[])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn query(&self, query: &Query) -> Result<Vec<WpmResult>, ErrorKind> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(from) = query.from {
+            conditions.push("timestamp >= ?");
+            values.push(Box::new(from.timestamp_micros()));
+        }
+        if let Some(to) = query.to {
+            conditions.push("timestamp < ?");
+            values.push(Box::new(to.timestamp_micros()));
+        }
+        if let Some(mode) = query.mode {
+            conditions.push("mode = ?");
+            values.push(Box::new(mode.to_string()));
+        }
+        if let Some(language) = &query.language {
+            conditions.push("language = ?");
+            values.push(Box::new(language.clone()));
+        }
+        if let Some(layout) = &query.layout {
+            conditions.push("layout = ?");
+            values.push(Box::new(layout.clone()));
+        }
+
+        let mut filter = String::new();
+        if !conditions.is_empty() {
+            filter.push_str(" WHERE ");
+            filter.push_str(&conditions.join(" AND "));
+        }
+        match query.top {
+            Some(top) => {
+                filter.push_str(" ORDER BY wpm DESC LIMIT ?");
+                values.push(Box::new(top as i64));
+            }
+            None => filter.push_str(" ORDER BY timestamp"),
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT date_time, wpm, accuracy, duration, awpm, mode, language, layout, source, id,
+                flags, integrity, tags, difficulty
+                FROM results{}",
+            filter
+        ))?;
+        let mut results = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((row.get::<_, i64>(9)?, result_from_row(row)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // The samples and replays of all the results at once, rather than two queries each
+        let mut timelines: HashMap<i64, Vec<Sample>> = HashMap::new();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT second, wpm, raw, errors, result_id FROM samples
+                WHERE result_id IN (SELECT id FROM results{})
+                ORDER BY result_id, second",
+            filter
+        ))?;
+        let mut rows = stmt.query(params_from_iter(values.iter()))?;
+        while let Some(row) = rows.next()? {
+            timelines.entry(row.get(4)?).or_default().push(sample_from_row(row)?);
+        }
+
+        let mut replays: HashMap<i64, Vec<Keystroke>> = HashMap::new();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT at_ms, key, correct, blocked, result_id FROM replays
+                WHERE result_id IN (SELECT id FROM results{})
+                ORDER BY result_id, seq",
+            filter
+        ))?;
+        let mut rows = stmt.query(params_from_iter(values.iter()))?;
+        while let Some(row) = rows.next()? {
+            replays.entry(row.get(4)?).or_default().push(keystroke_from_row(row)?);
+        }
+
+        for (id, result) in &mut results {
+            result.timeline = timelines.remove(id).unwrap_or_default();
+            verify(result, &replays.remove(id).unwrap_or_default());
+        }
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    fn replay(&self, date_time: &DateTime<Local>) -> Result<Vec<Keystroke>, ErrorKind> {
+        let mut stmt = self.conn.prepare(
+            "SELECT at_ms, key, correct, blocked FROM replays
+                JOIN results ON results.id = replays.result_id
+                WHERE results.timestamp = ?
+                ORDER BY seq",
+        )?;
+        let replay = stmt
+            .query_map([date_time.timestamp_micros()], keystroke_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(replay)
+    }
+
+    fn set_tags(
+        &mut self,
+        date_time: &DateTime<Local>,
+        tags: &[String],
+    ) -> Result<bool, ErrorKind> {
+        let updated = self.conn.execute(
+            "UPDATE results SET tags = ? WHERE timestamp = ?",
+            params![json_list(tags)?, date_time.timestamp_micros()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    fn delete(&mut self, date_time: &DateTime<Local>) -> Result<bool, ErrorKind> {
+        let tx = self.conn.transaction()?;
+        let id = match tx
+            .query_row(
+                "SELECT id FROM results WHERE timestamp = ?",
+                [date_time.timestamp_micros()],
+                |row| row.get(0),
+            )
+            .optional()?
+        {
+            Some(id) => id,
+            None => return Ok(false),
+        };
+        remove(&tx, id)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    fn key_stats(&self) -> Result<KeyStats, ErrorKind> {
+        let mut stmt = self.conn.prepare("SELECT key, hits, misses, total_ms FROM key_stats")?;
+        let stats = stmt
+            .query_map([], |row| {
+                let stat = KeyStat {
+                    hits: row.get(1)?,
+                    misses: row.get(2)?,
+                    total_time: Duration::from_millis(row.get(3)?),
+                };
+                Ok((single_char(0, row.get(0)?)?, stat))
+            })?
+            .collect::<rusqlite::Result<KeyStats>>()?;
+        Ok(stats)
+    }
+}
@@ -0,0 +1,320 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use chrono::{Local, TimeZone};
+    use crate::engine::engine::{KeyKind, Keystroke, Sample, StopOnError, TestMode};
+    use crate::history::browser::{next_mode, parse_tags, Column, DateRange, HistoryView, Playback};
+    use crate::history::history::{add_key_stats, KeyStats, Query, Store};
+    use crate::history::json_store::JsonStore;
+    use crate::serializers::wpm_results::WpmResult;
+    use crate::test_util::temp_path;
+    use crate::validation::validation::{seal, Flag};
+
+    fn result(day: u32, wpm: f64, mode: TestMode) -> WpmResult {
+        let mut result = WpmResult::new(wpm, 0.9, 0.5, wpm);
+        result.date_time = Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        result.mode = Some(mode);
+        result.language = Some("english".to_string());
+        result
+    }
+
+    fn key(ms: u64, key: KeyKind, correct: bool) -> Keystroke {
+        Keystroke {
+            at: Duration::from_millis(ms),
+            key,
+            correct,
+            blocked: false,
+        }
+    }
+
+    fn replay() -> Vec<Keystroke> {
+        vec![
+            key(0, KeyKind::Char('a'), true),
+            key(200, KeyKind::Char('b'), false),
+            key(300, KeyKind::Backspace, true),
+            key(500, KeyKind::Char('a'), true),
+        ]
+    }
+
+    fn check_store(store: &mut dyn Store) {
+        let mut timed = result(2, 60.0, TestMode::Time(30));
+        timed.timeline = vec![
+            Sample { second: 1.0, wpm: 36.0, raw: 48.0, errors: 1 },
+            Sample { second: 1.5, wpm: 40.0, raw: 24.0, errors: 0 },
+        ];
+        timed.flags = vec![Flag::PasteBurst];
+        // Typed stopping on letters, with a wrong key held back
+        timed.difficulty.stop_on_error = StopOnError::Letter;
+        let mut stopped = replay();
+        stopped.insert(3, Keystroke { blocked: true, ..key(400, KeyKind::Char('x'), false) });
+        seal(&mut timed, &stopped);
+        store.add(&timed, &stopped).unwrap();
+        store.add(&result(1, 80.0, TestMode::Words(25)), &[]).unwrap();
+        store.add(&result(3, 70.0, TestMode::Time(30)), &[]).unwrap();
+
+        let all = store.query(&Query::default()).unwrap();
+        assert_eq!(all.iter().map(|r| r.wpm).collect::<Vec<_>>(), vec![80.0, 60.0, 70.0]);
+        assert_eq!(all[0], result(1, 80.0, TestMode::Words(25)));
+        // Still matches its hash once read back
+        assert_eq!(all[1], timed);
+
+        let query = Query {
+            from: Some(Local.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap()),
+            mode: Some(TestMode::Time(30)),
+            ..Query::default()
+        };
+        assert_eq!(store.query(&query).unwrap().len(), 2);
+
+        let query = Query {
+            to: Some(Local.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap()),
+            top: Some(1),
+            ..Query::default()
+        };
+        assert_eq!(store.query(&query).unwrap()[0].wpm, 80.0);
+
+        let query = Query {
+            layout: Some("dvorak".to_string()),
+            ..Query::default()
+        };
+        assert!(store.query(&query).unwrap().is_empty());
+
+        assert_eq!(store.replay(&all[1].date_time).unwrap(), stopped);
+        assert!(store.replay(&all[0].date_time).unwrap().is_empty());
+
+        let stats = store.key_stats().unwrap();
+        assert_eq!(stats[&'a'].hits, 2);
+        assert_eq!(stats[&'b'].misses, 1);
+
+        // Tags are added after sealing and leave the hash alone
+        let tags = vec!["warmup".to_string(), "new keyboard".to_string()];
+        assert!(store.set_tags(&all[1].date_time, &tags).unwrap());
+        let tagged = store.query(&Query::default()).unwrap();
+        assert_eq!(tagged[1].tags, tags);
+        assert_eq!(tagged[1].flags, timed.flags);
+
+        assert!(store.delete(&all[1].date_time).unwrap());
+        assert!(!store.delete(&all[1].date_time).unwrap());
+        assert!(!store.set_tags(&all[1].date_time, &tags).unwrap());
+        let left = store.query(&Query::default()).unwrap();
+        assert_eq!(left.iter().map(|r| r.wpm).collect::<Vec<_>>(), vec![80.0, 70.0]);
+        assert!(store.replay(&all[1].date_time).unwrap().is_empty());
+        assert!(store.key_stats().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_key_stats() {
+        let mut stats = KeyStats::new();
+        add_key_stats(&mut stats, &replay());
+        add_key_stats(&mut stats, &replay());
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[&'a'].presses(), 4);
+        assert_eq!(stats[&'a'].accuracy(), 1.0);
+        // The second `a` follows the backspace
+        assert_eq!(stats[&'a'].total_time, Duration::from_millis(400));
+        assert_eq!(stats[&'b'].accuracy(), 0.0);
+        assert_eq!(stats[&'b'].mean_time(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_history_view() {
+        let mut results = vec![
+            result(1, 80.0, TestMode::Words(25)),
+            result(2, 60.0, TestMode::Time(30)),
+            result(3, 70.0, TestMode::Time(30)),
+        ];
+        results[1].tags = vec!["Warmup".to_string()];
+        let now = Local.with_ymd_and_hms(2024, 3, 3, 18, 0, 0).unwrap();
+
+        let mut view = HistoryView::new();
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![2, 1, 0]);
+
+        view.column = Column::Wpm;
+        view.descending = false;
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![1, 2, 0]);
+
+        // Time tests sort before word tests, ties stay in date order
+        view.column = Column::Mode;
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![1, 2, 0]);
+
+        view.filter.text = "warm".to_string();
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![1]);
+
+        view.filter.text = "words".to_string();
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![0]);
+
+        view.filter.text.clear();
+        view.filter.mode = Some(TestMode::Time(30));
+        view.filter.range = DateRange::Today;
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![2]);
+        assert_eq!(view.filter.describe(), "time 30, today");
+
+        view.filter.range = DateRange::Week;
+        view.refresh(&results, now);
+        assert_eq!(view.rows, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_next_mode() {
+        let results = vec![
+            result(1, 80.0, TestMode::Words(25)),
+            result(2, 60.0, TestMode::Time(30)),
+            result(3, 70.0, TestMode::Time(30)),
+        ];
+        assert_eq!(next_mode(None, &results), Some(TestMode::Time(30)));
+        assert_eq!(next_mode(Some(TestMode::Time(30)), &results), Some(TestMode::Words(25)));
+        assert_eq!(next_mode(Some(TestMode::Words(25)), &results), None);
+        assert_eq!(next_mode(None, &[]), None);
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags(" warmup, new keyboard,,warmup "), vec!["warmup", "new keyboard"]);
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_playback() {
+        let start = Instant::now();
+        let playback = Playback::new(replay(), start);
+        assert_eq!(playback.typed_at(start), "a");
+        assert_eq!(playback.typed_at(start + Duration::from_millis(250)), "ab");
+        assert_eq!(playback.typed_at(start + Duration::from_millis(300)), "a");
+        assert!(!playback.is_done_at(start + Duration::from_millis(300)));
+        assert_eq!(playback.typed_at(start + Duration::from_secs(1)), "aa");
+        assert!(playback.is_done_at(start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_json_store() {
+        let path = temp_path("bunbuntype_test_json_store.json");
+        let (mut store, errors) = JsonStore::open(&path);
+        assert!(errors.is_empty());
+        check_store(&mut store);
+
+        // Lines are still plain results to older readers
+        assert_eq!(WpmResult::from_file(&path).unwrap().len(), 2);
+
+        let (reopened, _) = JsonStore::open(&path);
+        assert_eq!(reopened.records(), store.records());
+    }
+
+    #[test]
+    fn test_json_store_keeps_unreadable_lines() {
+        let path = temp_path("bunbuntype_test_json_unreadable.json");
+        let (mut store, _) = JsonStore::open(&path);
+        store.add(&result(1, 50.0, TestMode::Time(15)), &[]).unwrap();
+        store.add(&result(2, 60.0, TestMode::Time(15)), &[]).unwrap();
+        std::fs::write(&path, std::fs::read_to_string(&path).unwrap() + "not json\n").unwrap();
+
+        // Editing the history writes the line it could not read back as it was
+        let (mut store, errors) = JsonStore::open(&path);
+        assert_eq!(errors.len(), 1);
+        let first = store.records()[0].result.date_time;
+        assert!(store.set_tags(&first, &["kept".to_string()]).unwrap());
+        assert!(store.delete(&first).unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.ends_with("not json\n"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store() {
+        use crate::history::sqlite_store::SqliteStore;
+
+        let mut store = SqliteStore::open(temp_path("bunbuntype_test_store.db")).unwrap();
+        check_store(&mut store);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_offset_change() {
+        use crate::history::sqlite_store::SqliteStore;
+        use chrono::FixedOffset;
+
+        let path = temp_path("bunbuntype_test_offset.db");
+        let mut store = SqliteStore::open(&path).unwrap();
+        let saved = result(4, 65.0, TestMode::Time(30));
+        store.add(&saved, &replay()).unwrap();
+
+        // The same moment written with the offset of another time zone
+        let moved = saved.date_time.with_timezone(&FixedOffset::east_opt(5 * 3600).unwrap());
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute("UPDATE results SET date_time = ?", [moved.to_rfc3339()]).unwrap();
+
+        assert_eq!(store.replay(&saved.date_time).unwrap(), replay());
+        assert!(store.set_tags(&saved.date_time, &["moved".to_string()]).unwrap());
+        assert!(store.delete(&saved.date_time).unwrap());
+        assert!(store.query(&Query::default()).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_migration() {
+        use crate::history::sqlite_store::SqliteStore;
+
+        let json = temp_path("bunbuntype_test_migration.json");
+        let (mut old, _) = JsonStore::open(&json);
+        old.add(&result(1, 50.0, TestMode::Time(15)), &replay()).unwrap();
+        std::fs::write(
+            &json,
+            std::fs::read_to_string(&json).unwrap() + "not json\n",
+        )
+        .unwrap();
+
+        let mut store = SqliteStore::open(temp_path("bunbuntype_test_migration.db")).unwrap();
+        let errors = store.migrate_from_json(&json).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(store.query(&Query::default()).unwrap().len(), 1);
+        assert_eq!(store.key_stats().unwrap()[&'a'].hits, 2);
+
+        // Until every line reads the import runs again, without adding anything twice
+        store.add(&result(2, 55.0, TestMode::Time(15)), &[]).unwrap();
+        assert_eq!(store.migrate_from_json(&json).unwrap().len(), 1);
+        assert_eq!(store.query(&Query::default()).unwrap().len(), 2);
+        assert_eq!(store.key_stats().unwrap()[&'a'].hits, 2);
+
+        // Then only the first clean open imports
+        let content = std::fs::read_to_string(&json).unwrap().replace("not json\n", "");
+        std::fs::write(&json, content).unwrap();
+        assert!(store.migrate_from_json(&json).unwrap().is_empty());
+        std::fs::write(&json, "not json\n").unwrap();
+        assert!(store.migrate_from_json(&json).unwrap().is_empty());
+        assert_eq!(store.query(&Query::default()).unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_unique_timestamp() {
+        use crate::history::sqlite_store::SqliteStore;
+
+        let path = temp_path("bunbuntype_test_unique.db");
+        let mut store = SqliteStore::open(&path).unwrap();
+        let saved = result(4, 65.0, TestMode::Time(30));
+        store.add(&saved, &replay()).unwrap();
+
+        // A database from when date_time was the key, holding one moment twice
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "DROP INDEX results_timestamp;
+            CREATE INDEX results_timestamp ON results (timestamp);
+            INSERT INTO results (timestamp, date_time, wpm, accuracy, duration, awpm)
+                SELECT timestamp, date_time || ' ', wpm, accuracy, duration, awpm FROM results;",
+        )
+        .unwrap();
+
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.query(&Query::default()).unwrap().len(), 1);
+        assert_eq!(store.replay(&saved.date_time).unwrap(), replay());
+        assert_eq!(store.key_stats().unwrap()[&'a'].hits, 2);
+        store.add(&saved, &replay()).unwrap();
+        assert_eq!(store.query(&Query::default()).unwrap().len(), 1);
+    }
+}
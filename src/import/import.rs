@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use csv::StringRecord;
+use crate::engine::engine::TestMode;
+use crate::error::ErrorKind;
+use crate::history::history::{Query, Store};
+use crate::serializers::wpm_results::WpmResult;
+
+/// The fields of a result a generic CSV column can be mapped to.
+pub const FIELDS: [&str; 9] = [
+    "date_time", "wpm", "awpm", "accuracy", "duration", "mode", "language", "layout", "source",
+];
+
+/// Which CSV column holds each field of a generic CSV. Defaults to the columns written
+/// by the CSV export, so exports can be imported back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    columns: Vec<(&'static str, String)>,
+    /// Whether accuracy is written as a percentage rather than a fraction, as exported.
+    pub percent_accuracy: bool,
+}
+
+impl Default for Mapping {
+    fn default() -> Self {
+        Mapping {
+            columns: FIELDS.iter().map(|f| (*f, f.to_string())).collect(),
+            percent_accuracy: false,
+        }
+    }
+}
+
+impl Mapping {
+    /// Reads the field from `column` instead.
+    pub fn set(&mut self, field: &str, column: &str) -> Result<(), ErrorKind> {
+        match self.columns.iter_mut().find(|(f, _)| *f == field) {
+            Some((_, c)) => {
+                *c = column.to_string();
+                Ok(())
+            }
+            None => Err(ErrorKind::InvalidArgument(format!(
+                "unknown field {}, expected one of {}",
+                field,
+                FIELDS.join(", ")
+            ))),
+        }
+    }
+
+    fn column<'a>(&'a self, field: &'a str) -> &'a str {
+        self.columns
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, c)| c.as_str())
+            .unwrap_or(field)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportSource {
+    /// The CSV export of monkeytype.com's account page.
+    Monkeytype,
+    /// A race history export from typeracer.com or tools following its columns.
+    Typeracer,
+    /// Any CSV, with a column for each field.
+    Csv(Mapping),
+}
+
+impl ImportSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportSource::Monkeytype => "monkeytype",
+            ImportSource::Typeracer => "typeracer",
+            ImportSource::Csv(_) => "csv",
+        }
+    }
+
+    /// Guesses the source from the header of the file.
+    pub fn detect(headers: &StringRecord) -> ImportSource {
+        let has = |name: &str| headers.iter().any(|h| h.trim().eq_ignore_ascii_case(name));
+        if has("rawWpm") && has("mode2") {
+            ImportSource::Monkeytype
+        } else if has("Race #") || has("Date/Time (UTC)") {
+            ImportSource::Typeracer
+        } else {
+            ImportSource::Csv(Mapping::default())
+        }
+    }
+
+    fn convert(&self, row: &Row) -> Result<WpmResult, String> {
+        let mut result = match self {
+            ImportSource::Monkeytype => {
+                // Monkeytype's wpm already takes mistakes into account, unlike ours
+                let net = parse_number(row.require("wpm")?)?;
+                let accuracy = parse_accuracy(row.require("acc")?, true)?;
+                let mut result = WpmResult::new(
+                    row.get("rawWpm").map(parse_number).transpose()?.unwrap_or(net),
+                    accuracy,
+                    row.get("testDuration").map(parse_number).transpose()?.unwrap_or(0.0) / 60.0,
+                    net,
+                );
+                result.date_time = parse_date_time(row.require("timestamp")?, false)?;
+                result.mode = match (row.get("mode"), row.get("mode2").map(str::parse)) {
+                    (Some("time"), Some(Ok(secs))) => Some(TestMode::Time(secs)),
+                    (Some("words"), Some(Ok(count))) => Some(TestMode::Words(count as usize)),
+                    _ => None,
+                };
+                result.language = row.get("language").map(str::to_string);
+                result
+            }
+            ImportSource::Typeracer => {
+                // Races only end once the text is typed correctly
+                let wpm = parse_number(row.require("WPM")?)?;
+                let accuracy = match row.get("Accuracy") {
+                    Some(accuracy) => parse_accuracy(accuracy, true)?,
+                    None => 1.0,
+                };
+                let mut result = WpmResult::new(wpm, accuracy, 0.0, wpm);
+                let date_time = row
+                    .get("Date/Time (UTC)")
+                    .or_else(|| row.get("Date"))
+                    .ok_or("missing column Date/Time (UTC)")?;
+                result.date_time = parse_date_time(date_time, true)?;
+                result
+            }
+            ImportSource::Csv(mapping) => {
+                let field = |name| row.get(mapping.column(name));
+                let number = |name| field(name).map(parse_number).transpose();
+                let wpm = parse_number(row.require(mapping.column("wpm"))?)?;
+                let accuracy = field("accuracy")
+                    .map(|a| parse_accuracy(a, mapping.percent_accuracy))
+                    .transpose()?;
+                let mut result = WpmResult::new(
+                    wpm,
+                    accuracy.unwrap_or(1.0),
+                    number("duration")?.unwrap_or(0.0),
+                    number("awpm")?.unwrap_or(wpm * accuracy.unwrap_or(1.0)),
+                );
+                let date_time = row.require(mapping.column("date_time"))?;
+                result.date_time = parse_date_time(date_time, false)?;
+                result.mode = field("mode")
+                    .map(|m| m.parse::<TestMode>())
+                    .transpose()
+                    .map_err(|e| e.to_string())?;
+                result.language = field("language").map(str::to_string);
+                result.layout = field("layout").map(str::to_string);
+                result.source = field("source").map(str::to_string);
+                result
+            }
+        };
+        if result.source.is_none() {
+            result.source = Some(self.name().to_string());
+        }
+        Ok(result)
+    }
+}
+
+impl std::str::FromStr for ImportSource {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "monkeytype" => Ok(ImportSource::Monkeytype),
+            "typeracer" => Ok(ImportSource::Typeracer),
+            "csv" => Ok(ImportSource::Csv(Mapping::default())),
+            _ => Err(ErrorKind::InvalidArgument(format!("unknown import source {}", s))),
+        }
+    }
+}
+
+/// A CSV record with its values looked up by header, ignoring case and blank values.
+struct Row<'a> {
+    headers: &'a StringRecord,
+    record: &'a StringRecord,
+}
+
+impl<'a> Row<'a> {
+    fn get(&self, column: &str) -> Option<&'a str> {
+        let record = self.record;
+        self.headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(column))
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    fn require(&self, column: &str) -> Result<&'a str, String> {
+        self.get(column).ok_or_else(|| format!("missing {}", column))
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    value
+        .trim_end_matches('%')
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))
+}
+
+/// Accuracy as a fraction. Values are taken as percentages when `percent` is set or they end
+/// with a percent sign, and as fractions otherwise.
+fn parse_accuracy(value: &str, percent: bool) -> Result<f64, String> {
+    let accuracy = parse_number(value)?;
+    match percent || value.ends_with('%') {
+        true => Ok(accuracy / 100.0),
+        false => Ok(accuracy),
+    }
+}
+
+/// Accepts RFC 3339, unix seconds or milliseconds, and `YYYY-MM-DD HH:MM:SS` taken as UTC
+/// or local time.
+fn parse_date_time(value: &str, naive_utc: bool) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("{} is not a date", value);
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.with_timezone(&Local));
+    }
+    if let Ok(number) = value.parse::<i64>() {
+        // Seconds would only reach this in the year 5138
+        let date_time = match number > 100_000_000_000 {
+            true => Utc.timestamp_millis_opt(number),
+            false => Utc.timestamp_opt(number, 0),
+        };
+        return date_time.single().map(|d| d.with_timezone(&Local)).ok_or_else(invalid);
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map_err(|_| invalid())?;
+    match naive_utc {
+        true => Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local)),
+        false => Local.from_local_datetime(&naive).earliest().ok_or_else(invalid),
+    }
+}
+
+/// A row that could not be turned into a result, by its line in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    pub line: u64,
+    pub reason: String,
+}
+
+/// Reads the results of a CSV file, guessing the source from its header when not given.
+pub fn read_csv(
+    reader: impl Read,
+    source: Option<ImportSource>,
+) -> Result<(ImportSource, Vec<WpmResult>, Vec<RowError>), ErrorKind> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let source = source.unwrap_or_else(|| ImportSource::detect(&headers));
+
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let converted = record.map_err(|e| {
+            let line = e.position().map(|p| p.line()).unwrap_or_default();
+            RowError { line, reason: e.to_string() }
+        });
+        let converted = converted.and_then(|record| {
+            let line = record.position().map(|p| p.line()).unwrap_or_default();
+            source
+                .convert(&Row { headers: &headers, record: &record })
+                .map_err(|reason| RowError { line, reason })
+        });
+        match converted {
+            Ok(result) => results.push(result),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    Ok((source, results, errors))
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    pub source: String,
+    pub imported: usize,
+    /// Results taken at the same moment as one already in the history.
+    pub duplicates: usize,
+    pub failed: Vec<RowError>,
+}
+
+impl std::fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Imported {} {} results, skipped {} duplicates",
+            self.imported, self.source, self.duplicates
+        )?;
+        if !self.failed.is_empty() {
+            write!(f, ", {} rows failed:", self.failed.len())?;
+            for e in &self.failed {
+                write!(f, "\n  line {}: {}", e.line, e.reason)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds `results` to `history`, skipping any taken in the same millisecond as a result
+/// already there or earlier in `results`.
+pub fn import(
+    history: &mut dyn Store,
+    results: Vec<WpmResult>,
+) -> Result<(usize, usize), ErrorKind> {
+    let mut seen = history
+        .query(&Query::default())?
+        .iter()
+        .map(|r| r.date_time.timestamp_millis())
+        .collect::<HashSet<_>>();
+    let total = results.len();
+    let fresh = results
+        .into_iter()
+        .filter(|r| seen.insert(r.date_time.timestamp_millis()))
+        .collect::<Vec<_>>();
+    history.add_all(&fresh)?;
+    Ok((fresh.len(), total - fresh.len()))
+}
+
+/// Imports the CSV file at `path` into `history`.
+pub fn import_file(
+    history: &mut dyn Store,
+    path: impl AsRef<Path>,
+    source: Option<ImportSource>,
+) -> Result<ImportReport, ErrorKind> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).map_err(|e| ErrorKind::FileError(path.to_path_buf(), e))?;
+    let (source, results, failed) = read_csv(file, source)?;
+    let (imported, duplicates) = import(history, results)?;
+    Ok(ImportReport {
+        source: source.name().to_string(),
+        imported,
+        duplicates,
+        failed,
+    })
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod import;
+#[allow(clippy::module_inception)]
+mod tests;
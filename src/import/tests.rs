@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use crate::engine::engine::TestMode;
+    use crate::export::export::{export, ExportFormat};
+    use crate::history::history::{Query, Store};
+    use crate::history::json_store::JsonStore;
+    use crate::import::import::{import, read_csv, ImportSource, Mapping};
+    use crate::serializers::wpm_results::WpmResult;
+    use crate::test_util::temp_path;
+
+    const MONKEYTYPE: &str = "\
+_id,isPb,wpm,acc,rawWpm,consistency,charStats,mode,mode2,quoteLength,restartCount,testDuration,afkDuration,incompleteTestSeconds,lazyMode,blindMode,bailedOut,tags,timestamp,language,funbox,difficulty,numbers,punctuation
+a1,true,92.4,97.5,95.1,80.2,\"230;4;0;1\",time,30,-1,0,30.0,0,0,false,false,false,,1700000000000,english,none,normal,false,false
+a2,false,70,96,72,75,\"180;5;0;0\",words,25,-1,1,21.5,0,0,false,false,false,,1700000100000,english_1k,none,normal,false,false
+a3,false,fast,96,72,75,\"180;5;0;0\",quote,,2,1,21.5,0,0,false,false,false,,1700000200000,english,none,normal,false,false
+";
+
+    const TYPERACER: &str = "\
+Race #,WPM,Accuracy,Rank,# Racers,Text ID,Date/Time (UTC)
+12,88,97,1,5,3550123,2023-11-14 22:15:00
+11,79,95%,2,5,3550456,2023-11-14 22:10:00
+10,81,99,3,5,3550789,yesterday
+";
+
+    #[test]
+    fn test_read_monkeytype() {
+        let (source, results, errors) = read_csv(MONKEYTYPE.as_bytes(), None).unwrap();
+
+        assert_eq!(source, ImportSource::Monkeytype);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].wpm, 95.1);
+        assert_eq!(results[0].awpm, 92.4);
+        assert_eq!(results[0].accuracy, 0.975);
+        assert_eq!(results[0].duration, 0.5);
+        assert_eq!(results[0].mode, Some(TestMode::Time(30)));
+        assert_eq!(results[0].date_time, Utc.timestamp_millis_opt(1700000000000).unwrap());
+        assert_eq!(results[0].source.as_deref(), Some("monkeytype"));
+        assert_eq!(results[1].mode, Some(TestMode::Words(25)));
+        assert_eq!(results[1].language.as_deref(), Some("english_1k"));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+        assert!(errors[0].reason.contains("fast"));
+    }
+
+    #[test]
+    fn test_read_typeracer() {
+        let (source, results, errors) = read_csv(TYPERACER.as_bytes(), None).unwrap();
+
+        assert_eq!(source, ImportSource::Typeracer);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].date_time, Utc.with_ymd_and_hms(2023, 11, 14, 22, 15, 0).unwrap());
+        assert_eq!(results[0].accuracy, 0.97);
+        assert_eq!(results[1].accuracy, 0.95);
+        assert_eq!(results[1].awpm, 79.0);
+        assert_eq!(errors[0].line, 4);
+    }
+
+    #[test]
+    fn test_read_mapped_csv() {
+        let content = "When,Speed,Acc\n2023-11-14T10:00:00Z,55.5,0.9\n2023-11-15T10:00:00Z,,0.9\n";
+        let mut mapping = Mapping::default();
+        mapping.set("date_time", "When").unwrap();
+        mapping.set("wpm", "Speed").unwrap();
+        mapping.set("accuracy", "acc").unwrap();
+        assert!(mapping.set("speed", "Speed").is_err());
+
+        let source = Some(ImportSource::Csv(mapping));
+        let (_, results, errors) = read_csv(content.as_bytes(), source).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].awpm, 55.5 * 0.9);
+        assert_eq!(results[0].source.as_deref(), Some("csv"));
+        assert_eq!(errors[0].reason, "missing Speed");
+    }
+
+    #[test]
+    fn test_read_accuracy_scale() {
+        let content = "date_time,wpm,accuracy\n2023-11-14T10:00:00Z,50,1\n\
+            2023-11-15T10:00:00Z,50,0.5\n";
+        let (_, results, _) = read_csv(content.as_bytes(), None).unwrap();
+        assert_eq!(results[0].accuracy, 1.0);
+        assert_eq!(results[1].accuracy, 0.5);
+
+        // The scale comes from the mapping, not from how large the value is
+        let mut mapping = Mapping::default();
+        mapping.percent_accuracy = true;
+        let source = Some(ImportSource::Csv(mapping));
+        let (_, results, _) = read_csv(content.as_bytes(), source).unwrap();
+        assert_eq!(results[0].accuracy, 0.01);
+        assert_eq!(results[1].accuracy, 0.005);
+    }
+
+    #[test]
+    fn test_import_own_export() {
+        let mut result = WpmResult::new(60.0, 0.95, 0.5, 57.0);
+        result.mode = Some(TestMode::Words(10));
+        result.layout = Some("colemak".to_string());
+        let csv = export(&[result.clone()], ExportFormat::Csv).unwrap();
+
+        let (_, results, errors) = read_csv(csv.as_bytes(), None).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(results[0].mode, result.mode);
+        assert_eq!(results[0].layout, result.layout);
+        assert_eq!(results[0].date_time, result.date_time);
+    }
+
+    #[test]
+    fn test_import_dedupes() {
+        let path = temp_path("bunbuntype_test_import.json");
+        let (mut history, _) = JsonStore::open(&path);
+
+        let (_, results, _) = read_csv(TYPERACER.as_bytes(), None).unwrap();
+        let mut twice = results.clone();
+        twice.extend(results.clone());
+        assert_eq!(import(&mut history, twice).unwrap(), (2, 2));
+        assert_eq!(import(&mut history, results).unwrap(), (0, 2));
+        assert_eq!(history.query(&Query::default()).unwrap().len(), 2);
+    }
+}
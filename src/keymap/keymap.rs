@@ -0,0 +1,539 @@
+use std::collections::{BTreeMap, HashMap};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::error::ErrorKind;
+
+/// Key overrides as written in the config: screen name -> action name -> chords.
+pub type KeymapConfig = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Screen {
+    Menu,
+    Test,
+    Typing,
+    Chart,
+    Settings,
+    Timer,
+    Profiles,
+    Stats,
+    History,
+}
+
+impl Screen {
+    pub const ALL: [Screen; 9] = [
+        Screen::Menu,
+        Screen::Test,
+        Screen::Typing,
+        Screen::Chart,
+        Screen::Settings,
+        Screen::Timer,
+        Screen::Profiles,
+        Screen::Stats,
+        Screen::History,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Screen::Menu => "menu",
+            Screen::Test => "test",
+            Screen::Typing => "typing",
+            Screen::Chart => "chart",
+            Screen::Settings => "settings",
+            Screen::Timer => "timer",
+            Screen::Profiles => "profiles",
+            Screen::Stats => "stats",
+            Screen::History => "history",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Screen> {
+        Screen::ALL.iter().copied().find(|s| s.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    StartTyping,
+    PauseTest,
+    RestartTest,
+    NextTest,
+    AbandonTest,
+    EditTimer,
+    ToggleTimer,
+    ResetTimer,
+    OpenSettings,
+    ToggleChartFilter,
+    Help,
+    Suspend,
+    Export,
+    NewProfile,
+    RenameProfile,
+    DeleteProfile,
+    SortHistory,
+    ReverseHistory,
+    FilterHistory,
+    FilterMode,
+    FilterDates,
+    ReplayResult,
+    TagResult,
+    DeleteResult,
+}
+
+impl Action {
+    pub const ALL: [Action; 31] = [
+        Action::Quit,
+        Action::Back,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Select,
+        Action::StartTyping,
+        Action::PauseTest,
+        Action::RestartTest,
+        Action::NextTest,
+        Action::AbandonTest,
+        Action::EditTimer,
+        Action::ToggleTimer,
+        Action::ResetTimer,
+        Action::OpenSettings,
+        Action::ToggleChartFilter,
+        Action::Help,
+        Action::Suspend,
+        Action::Export,
+        Action::NewProfile,
+        Action::RenameProfile,
+        Action::DeleteProfile,
+        Action::SortHistory,
+        Action::ReverseHistory,
+        Action::FilterHistory,
+        Action::FilterMode,
+        Action::FilterDates,
+        Action::ReplayResult,
+        Action::TagResult,
+        Action::DeleteResult,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Back => "back",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Select => "select",
+            Action::StartTyping => "start_typing",
+            Action::PauseTest => "pause_test",
+            Action::RestartTest => "restart_test",
+            Action::NextTest => "next_test",
+            Action::AbandonTest => "abandon_test",
+            Action::EditTimer => "edit_timer",
+            Action::ToggleTimer => "toggle_timer",
+            Action::ResetTimer => "reset_timer",
+            Action::OpenSettings => "open_settings",
+            Action::ToggleChartFilter => "toggle_chart_filter",
+            Action::Help => "help",
+            Action::Suspend => "suspend",
+            Action::Export => "export",
+            Action::NewProfile => "new_profile",
+            Action::RenameProfile => "rename_profile",
+            Action::DeleteProfile => "delete_profile",
+            Action::SortHistory => "sort_history",
+            Action::ReverseHistory => "reverse_history",
+            Action::FilterHistory => "filter_history",
+            Action::FilterMode => "filter_mode",
+            Action::FilterDates => "filter_dates",
+            Action::ReplayResult => "replay_result",
+            Action::TagResult => "tag_result",
+            Action::DeleteResult => "delete_result",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// A key together with its modifiers, e.g. `ctrl-r`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is already part of the character, `?` arrives as shift + '?' on most terminals,
+        // and shift-tab arrives as a back tab that may or may not carry the shift modifier
+        match code {
+            KeyCode::Char(_) | KeyCode::BackTab => Self {
+                code,
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::BackTab,
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => Self { code, modifiers },
+        }
+    }
+
+    /// Parses chords such as `q`, `?`, `esc`, `ctrl-r` or `shift-tab`.
+    pub fn parse(chord: &str) -> Result<KeyChord, ErrorKind> {
+        let invalid = || ErrorKind::InvalidKey(chord.to_string());
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = chord;
+
+        // A lone "-" is a key, so only split on dashes followed by something
+        while let Some((prefix, tail)) = rest.split_once('-') {
+            if tail.is_empty() {
+                break;
+            }
+            modifiers |= match prefix.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+            rest = tail;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') => match f[1..].parse::<u8>() {
+                Ok(n) => KeyCode::F(n),
+                Err(_) => return Err(invalid()),
+            },
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+
+        Ok(KeyChord::new(code, modifiers))
+    }
+
+    /// Whether the chord would otherwise be typed as text while typing.
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(key: &KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Two or more actions bound to the same chord on one screen, or a text key bound while typing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub screen: Screen,
+    pub chord: KeyChord,
+    pub actions: Vec<Action>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let actions = self.actions.iter().map(|a| a.name()).collect::<Vec<_>>();
+        if actions.len() == 1 {
+            write!(f, "{}: '{}' ({}) shadows text input", self.screen.name(), self.chord, actions[0])
+        } else {
+            write!(f, "{}: '{}' is bound to {}", self.screen.name(), self.chord, actions.join(", "))
+        }
+    }
+}
+
+type ScreenBindings = &'static [(Action, &'static [&'static str])];
+
+/// Vim-style bindings, used for every action the config does not override.
+const DEFAULT_BINDINGS: &[(Screen, ScreenBindings)] = &[
+    (
+        Screen::Menu,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Select, &["enter", "l"]),
+            (Action::OpenSettings, &["s"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Test,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "esc"]),
+            (Action::StartTyping, &["i", "enter"]),
+            (Action::RestartTest, &["r"]),
+            (Action::NextTest, &["n"]),
+            (Action::AbandonTest, &["x"]),
+            (Action::EditTimer, &["t"]),
+            (Action::OpenSettings, &["s"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Typing,
+        &[
+            (Action::PauseTest, &["esc"]),
+            (Action::RestartTest, &["tab"]),
+            (Action::NextTest, &["ctrl-n"]),
+            (Action::AbandonTest, &["ctrl-x"]),
+            (Action::Quit, &["ctrl-c"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Chart,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "h", "esc"]),
+            (Action::ToggleChartFilter, &["f"]),
+            (Action::Export, &["e"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Settings,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "esc"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right", "enter"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Timer,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "esc"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Left, &["h", "left"]),
+            (Action::Right, &["l", "right"]),
+            (Action::Select, &["enter"]),
+            (Action::ToggleTimer, &["space", "p"]),
+            (Action::ResetTimer, &["r"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Profiles,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "esc"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Select, &["enter", "l"]),
+            (Action::NewProfile, &["n"]),
+            (Action::RenameProfile, &["r"]),
+            (Action::DeleteProfile, &["d"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::Stats,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "h", "esc"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+    (
+        Screen::History,
+        &[
+            (Action::Quit, &["q", "ctrl-c"]),
+            (Action::Back, &["b", "h", "esc"]),
+            (Action::Up, &["k", "up"]),
+            (Action::Down, &["j", "down"]),
+            (Action::Select, &["enter", "l"]),
+            (Action::SortHistory, &["o"]),
+            (Action::ReverseHistory, &["r"]),
+            (Action::FilterHistory, &["/"]),
+            (Action::FilterMode, &["m"]),
+            (Action::FilterDates, &["w"]),
+            (Action::ReplayResult, &["p"]),
+            (Action::TagResult, &["t"]),
+            (Action::DeleteResult, &["d"]),
+            (Action::Help, &["?"]),
+            (Action::Suspend, &["ctrl-z"]),
+        ],
+    ),
+];
+
+pub struct Keymap {
+    bindings: HashMap<Screen, Vec<(Action, Vec<KeyChord>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(screen, actions)| {
+                let actions = actions
+                    .iter()
+                    .map(|(action, chords)| {
+                        let chords = chords
+                            .iter()
+                            .map(|c| KeyChord::parse(c).expect("invalid default key"))
+                            .collect();
+                        (*action, chords)
+                    })
+                    .collect();
+                (*screen, actions)
+            })
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default keymap with the bindings from `config` replacing the defaults of the
+    /// actions they name. An empty list unbinds the action.
+    pub fn from_config(config: &KeymapConfig) -> Result<Keymap, ErrorKind> {
+        let mut keymap = Keymap::default();
+
+        for (screen_name, actions) in config {
+            let screen = Screen::from_name(screen_name)
+                .ok_or_else(|| ErrorKind::InvalidKey(format!("unknown screen '{}'", screen_name)))?;
+
+            for (action_name, chords) in actions {
+                let action = Action::from_name(action_name)
+                    .ok_or_else(|| ErrorKind::InvalidKey(format!("unknown action '{}'", action_name)))?;
+                let chords = chords
+                    .iter()
+                    .map(|c| KeyChord::parse(c))
+                    .collect::<Result<Vec<_>, _>>()?;
+                keymap.bind(screen, action, chords);
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn bind(&mut self, screen: Screen, action: Action, chords: Vec<KeyChord>) {
+        let bindings = self.bindings.entry(screen).or_default();
+        match bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, existing)) => *existing = chords,
+            None => bindings.push((action, chords)),
+        }
+    }
+
+    /// The action bound to `key` on `screen`. On conflicts the first listed action wins.
+    pub fn action(&self, screen: Screen, key: &KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(key);
+        self.bindings
+            .get(&screen)?
+            .iter()
+            .find(|(_, chords)| chords.contains(&chord))
+            .map(|(action, _)| *action)
+    }
+
+    /// The bindings of `screen` in display order.
+    pub fn bindings(&self, screen: Screen) -> Vec<(Action, Vec<KeyChord>)> {
+        self.bindings.get(&screen).cloned().unwrap_or_default()
+    }
+
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+
+        for screen in Screen::ALL {
+            let mut by_chord: Vec<(KeyChord, Vec<Action>)> = Vec::new();
+            for (action, chords) in self.bindings(screen) {
+                for chord in chords {
+                    match by_chord.iter_mut().find(|(c, _)| *c == chord) {
+                        Some((_, actions)) => {
+                            if !actions.contains(&action) {
+                                actions.push(action);
+                            }
+                        }
+                        None => by_chord.push((chord, vec![action])),
+                    }
+                }
+            }
+
+            for (chord, actions) in by_chord {
+                if actions.len() > 1 || (screen == Screen::Typing && chord.is_text()) {
+                    conflicts.push(Conflict {
+                        screen,
+                        chord,
+                        actions,
+                    });
+                }
+            }
+        }
+
+        conflicts
+    }
+}
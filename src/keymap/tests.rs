@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::keymap::keymap::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::BTreeMap;
+
+    fn config(screen: &str, action: &str, chords: &[&str]) -> KeymapConfig {
+        let mut actions = BTreeMap::new();
+        actions.insert(
+            action.to_string(),
+            chords.iter().map(|c| c.to_string()).collect(),
+        );
+        let mut config = KeymapConfig::new();
+        config.insert(screen.to_string(), actions);
+        config
+    }
+
+    #[test]
+    fn test_parse_chord() {
+        let chord = KeyChord::parse("ctrl-r").unwrap();
+        assert_eq!(chord.code, KeyCode::Char('r'));
+        assert_eq!(chord.modifiers, KeyModifiers::CONTROL);
+
+        assert_eq!(KeyChord::parse("-").unwrap().code, KeyCode::Char('-'));
+        assert_eq!(KeyChord::parse("F5").unwrap().code, KeyCode::F(5));
+        assert_eq!(KeyChord::parse("shift-tab").unwrap().code, KeyCode::BackTab);
+        assert_eq!(KeyChord::parse("ctrl-r").unwrap().to_string(), "ctrl-r");
+        assert!(KeyChord::parse("hyper-x").is_err());
+        assert!(KeyChord::parse("nope").is_err());
+    }
+
+    #[test]
+    fn test_default_actions() {
+        let keymap = Keymap::default();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(keymap.action(Screen::Menu, &key(KeyCode::Char('j'))), Some(Action::Down));
+        assert_eq!(keymap.action(Screen::Menu, &key(KeyCode::Char('q'))), Some(Action::Quit));
+        assert_eq!(keymap.action(Screen::Typing, &key(KeyCode::Char('q'))), None);
+        assert_eq!(
+            keymap.action(Screen::Menu, &KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            Some(Action::Help)
+        );
+        assert!(keymap.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_config_override() {
+        let keymap = Keymap::from_config(&config("menu", "quit", &["x"])).unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        assert_eq!(keymap.action(Screen::Menu, &key('x')), Some(Action::Quit));
+        assert_eq!(keymap.action(Screen::Menu, &key('q')), None);
+        assert!(Keymap::from_config(&config("nowhere", "quit", &["x"])).is_err());
+        assert!(Keymap::from_config(&config("menu", "fly", &["x"])).is_err());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let keymap = Keymap::from_config(&config("menu", "quit", &["j"])).unwrap();
+        let conflicts = keymap.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].actions, vec![Action::Quit, Action::Down]);
+
+        let keymap = Keymap::from_config(&config("typing", "restart_test", &["r"])).unwrap();
+        assert_eq!(keymap.conflicts().len(), 1);
+    }
+}
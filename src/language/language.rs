@@ -0,0 +1,125 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::error::ErrorKind;
+use crate::loaders::loader::load_wordlist;
+
+pub const LANGUAGES_DIR: &str = "resource/languages";
+/// The language of the plain wordlist every install has.
+pub const DEFAULT_LANGUAGE: &str = "english";
+
+/// Which way a language is written.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Left to right, like English.
+    #[default]
+    Ltr,
+    /// Right to left, like Arabic and Hebrew.
+    Rtl,
+}
+
+impl Direction {
+    /// `text` in the order its graphemes appear on screen. Terminals lay out cells left to
+    /// right, so right to left text is reversed here; each grapheme keeps its combining marks.
+    pub fn visual(&self, text: &str) -> String {
+        match self {
+            Direction::Ltr => text.to_string(),
+            Direction::Rtl => text.graphemes(true).rev().collect(),
+        }
+    }
+}
+
+/// Words to type along with how they are written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Language {
+    pub name: String,
+    pub direction: Direction,
+    pub words: Vec<String>,
+}
+
+/// A language pack as written in a TOML file.
+#[derive(Deserialize)]
+struct LanguageFile {
+    name: Option<String>,
+    #[serde(default)]
+    direction: Direction,
+    words: Vec<String>,
+}
+
+impl Language {
+    /// The default language, read from a plain wordlist.
+    pub fn from_wordlist(path: impl AsRef<Path>) -> Result<Language, ErrorKind> {
+        Ok(Language {
+            name: DEFAULT_LANGUAGE.to_string(),
+            direction: Direction::Ltr,
+            words: load_wordlist(path)?,
+        })
+    }
+
+    /// `default` followed by every valid `*.toml` language pack in `dir`, along with an error
+    /// for each pack that could not be used. A pack named like `default` replaces it.
+    pub fn load_all(dir: impl AsRef<Path>, default: Language) -> (Vec<Language>, Vec<ErrorKind>) {
+        let mut languages = vec![default];
+
+        let mut paths = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            match Language::from_file(&path) {
+                Ok(language) => match languages.iter_mut().find(|l| l.name == language.name) {
+                    Some(existing) => *existing = language,
+                    None => languages.push(language),
+                },
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (languages, errors)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Language, ErrorKind> {
+        let path = path.as_ref();
+        let language_error =
+            |e: ErrorKind| ErrorKind::LanguageError(path.to_path_buf(), Box::new(e));
+        let content = std::fs::read_to_string(path).map_err(|e| language_error(e.into()))?;
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Language::from_toml(&content, &stem).map_err(language_error)
+    }
+
+    /// Parses a language pack, naming it `default_name` if the file has no `name` key.
+    pub fn from_toml(content: &str, default_name: &str) -> Result<Language, ErrorKind> {
+        let file: LanguageFile = toml::from_str(content)?;
+        let words = file
+            .words
+            .iter()
+            .flat_map(|w| w.split_whitespace())
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return Err(ErrorKind::InvalidLanguage("no words found".to_string()));
+        }
+
+        Ok(Language {
+            name: file.name.unwrap_or_else(|| default_name.to_string()),
+            direction: file.direction,
+            words,
+        })
+    }
+}
+
+/// Picks `name` from `languages`, falling back to the first one when `name` is unknown.
+pub fn select_language<'a>(languages: &'a [Language], name: &str) -> &'a Language {
+    languages.iter().find(|l| l.name == name).unwrap_or(&languages[0])
+}
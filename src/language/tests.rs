@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use crate::language::language::*;
+
+    #[test]
+    fn test_from_toml() {
+        let language =
+            Language::from_toml("direction = \"rtl\"\nwords = [\"שלום\", \"עולם טוב\"]", "hebrew")
+                .unwrap();
+        assert_eq!(language.name, "hebrew");
+        assert_eq!(language.direction, Direction::Rtl);
+        assert_eq!(language.words, vec!["שלום", "עולם", "טוב"]);
+
+        let language = Language::from_toml("name = \"latin\"\nwords = [\"lorem\"]", "x").unwrap();
+        assert_eq!(language.name, "latin");
+        assert_eq!(language.direction, Direction::Ltr);
+
+        assert!(Language::from_toml("words = []", "empty").is_err());
+        assert!(Language::from_toml("direction = \"up\"\nwords = [\"a\"]", "bad").is_err());
+    }
+
+    #[test]
+    fn test_visual() {
+        assert_eq!(Direction::Ltr.visual("abc"), "abc");
+        assert_eq!(Direction::Rtl.visual("שלום"), "םולש");
+
+        // Harakat stay on the letter they belong to
+        assert_eq!(Direction::Rtl.visual("كَتَب"), "بتَكَ");
+    }
+
+    #[test]
+    fn test_bundled_packs() {
+        let default = Language {
+            name: DEFAULT_LANGUAGE.to_string(),
+            direction: Direction::Ltr,
+            words: vec!["cat".to_string()],
+        };
+        let (languages, errors) = Language::load_all(LANGUAGES_DIR, default);
+
+        assert!(errors.is_empty());
+        assert_eq!(languages[0].name, DEFAULT_LANGUAGE);
+        assert_eq!(select_language(&languages, "hebrew").direction, Direction::Rtl);
+        assert_eq!(select_language(&languages, "arabic").direction, Direction::Rtl);
+        assert_eq!(select_language(&languages, "klingon").name, DEFAULT_LANGUAGE);
+    }
+}
@@ -3,19 +3,25 @@ use std::io;
 use std::io::BufRead;
 use std::path::Path;
 use rand::seq::SliceRandom;
+use crate::error::ErrorKind;
 
-pub fn load_wordlist<P>(path: P) -> Vec<String>
+pub fn load_wordlist<P>(path: P) -> Result<Vec<String>, ErrorKind>
     where
         P: AsRef<Path>,
 {
+    let path = path.as_ref();
     let mut rng = rand::thread_rng();
-    let mut wordlist = std::fs::read_to_string(path).unwrap();
+    let wordlist = std::fs::read_to_string(path)
+        .map_err(|e| ErrorKind::FileError(path.to_path_buf(), e))?;
     let mut wordlist: Vec<String> = wordlist
         .split_whitespace()
         .map(|s| s.to_string())
         .collect();
+    if wordlist.is_empty() {
+        return Err(ErrorKind::WordlistError(path.to_path_buf(), "no words found".to_string()));
+    }
     wordlist.shuffle(&mut rng);
-    wordlist
+    Ok(wordlist)
 }
 
 pub fn read_lines<P>(filename: P) -> io::Result<Vec<String>>
@@ -23,8 +29,7 @@ pub fn read_lines<P>(filename: P) -> io::Result<Vec<String>>
         P: AsRef<Path>,
 {
     let file = File::open(filename)?;
-    Ok(io::BufReader::new(file)
+    io::BufReader::new(file)
         .lines()
-        .map(|x| x.expect("Fail"))
-        .collect::<Vec<String>>())
+        .collect::<io::Result<Vec<String>>>()
 }
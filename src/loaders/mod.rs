@@ -1,3 +1,4 @@
 pub mod loader;
 pub mod randomizer;
+#[allow(clippy::module_inception)]
 pub mod tests;
\ No newline at end of file
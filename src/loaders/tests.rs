@@ -1,17 +1,21 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::loaders::loader::load_wordlist;
     use crate::loaders::randomizer::{charize, randomizer};
-    use rand::random;
 
     #[test]
     fn test_loaders() {
-        let load = load_wordlist("resource/wordlist");
+        let load = load_wordlist("resource/wordlist").unwrap();
         let random = randomizer(&load);
         assert!(random.is_ok());
     }
 
+    #[test]
+    fn test_load_missing_wordlist() {
+        let err = load_wordlist("resource/missing_wordlist").unwrap_err();
+        assert!(err.to_string().starts_with("resource/missing_wordlist: "));
+    }
+
     #[test]
     fn test_charize() {
         let word = String::from("test");
@@ -2,45 +2,148 @@ extern crate core;
 
 mod app;
 mod calculators;
+pub mod cli;
+mod config;
+mod engine;
 mod error;
+pub mod export;
+mod goals;
+pub mod history;
+pub mod import;
+mod keymap;
+pub mod language;
 pub mod loaders;
+pub mod profile;
 pub mod serializers;
+mod sound;
+mod stats;
+#[cfg(test)]
+mod test_util;
+pub mod theme;
 pub mod timer;
+pub mod validation;
 mod ui;
 
 use crate::app::App;
+use crate::cli::cli::{Args, Command, USAGE};
+use crate::config::config::Config;
+use crate::error::ErrorKind;
+use crate::history::history::Store;
+use crate::profile::profile::{Profile, Profiles, PROFILES_DIR};
+use crate::ui::plain::run_plain;
+use crate::ui::terminal::{install_panic_hook, Signals, TerminalGuard};
 use crate::ui::ui::run_app;
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
 use std::error::Error;
 use std::io;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let mut app = App::new("resource/wordlist", "resource/score.json");
-    let res = run_app(&mut terminal, &mut app);
-
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    install_panic_hook();
 
-    if let Err(err) = res {
-        println!("{}", err)
+    let Args { profile, command } = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Settings and history used to live directly in resource, they become the default profile
+    let profiles = match Profiles::open(PROFILES_DIR, "resource") {
+        Ok(profiles) => profiles,
+        Err(e) => exit_with(e),
+    };
+
+    match command {
+        Command::Run | Command::Plain => {}
+        Command::Help => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        Command::Export(args) => {
+            exit_on_error(
+                command_profile(&profiles, profile.as_deref())
+                    .and_then(|p| open_history(&p))
+                    .and_then(|h| args.run(h.as_ref())),
+            );
+            return Ok(());
+        }
+        Command::Import(args) => {
+            exit_on_error(
+                command_profile(&profiles, profile.as_deref())
+                    .and_then(|p| open_history(&p))
+                    .and_then(|mut h| args.run(h.as_mut())),
+            );
+            return Ok(());
+        }
     }
 
+    // The selector is shown on startup when there is a choice and none was made
+    let plain = command == Command::Plain;
+    let choose_profile =
+        !plain && profile.is_none() && profiles.list().is_ok_and(|l| l.len() > 1);
+    let profile = match profile {
+        Some(name) => profiles.get(&name).or_else(|_| profiles.create(&name)),
+        None => profiles.last(),
+    };
+    let profile = match profile.and_then(|p| profiles.set_last(&p.name).map(|()| p)) {
+        Ok(profile) => profile,
+        Err(e) => exit_with(e),
+    };
+
+    let signals = Signals::register()?;
+
+    if plain {
+        let res = App::new("resource/wordlist", profiles, profile, false)
+            .and_then(|mut app| run_plain(&mut app, &signals));
+        exit_on_error(res);
+        return Ok(());
+    }
+
+    let res = {
+        // Restores the terminal when dropped, whether the app quit, failed or panicked
+        let _guard = TerminalGuard::new()?;
+        let backend = CrosstermBackend::new(io::stdout());
+        let mut terminal = Terminal::new(backend)?;
+
+        App::new("resource/wordlist", profiles, profile, choose_profile)
+            .and_then(|mut app| run_app(&mut terminal, &mut app, &signals))
+    };
+
+    exit_on_error(res);
     Ok(())
 }
+
+/// The profile a command works on, which has to exist already.
+fn command_profile(profiles: &Profiles, name: Option<&str>) -> Result<Profile, ErrorKind> {
+    match name {
+        Some(name) => profiles.get(name),
+        None => profiles.last(),
+    }
+}
+
+/// Opens the history of `profile` for a command, printing problems that do not stop it.
+fn open_history(profile: &Profile) -> Result<Box<dyn Store>, ErrorKind> {
+    // A broken config is not fatal, the defaults are used
+    let config = Config::load(profile.config_file()).unwrap_or_else(|e| {
+        eprintln!("Warning: {}", e);
+        Config::default()
+    });
+    let (history, errors) = history::history::open(config.history, profile.history_file())?;
+    for e in errors {
+        eprintln!("Warning: {}", e);
+    }
+    Ok(history)
+}
+
+fn exit_on_error(res: Result<(), ErrorKind>) {
+    if let Err(err) = res {
+        exit_with(err);
+    }
+}
+
+fn exit_with(err: ErrorKind) -> ! {
+    eprintln!("Error: {}", err);
+    std::process::exit(1);
+}
@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+use crate::error::ErrorKind;
+
+pub const PROFILES_DIR: &str = "resource/profiles";
+/// The profile made for existing users, who had a single config and history.
+pub const DEFAULT_PROFILE: &str = "default";
+/// Holds the name of the profile used last, to start with it next time.
+const LAST_FILE: &str = ".last";
+/// Files of a profile, which lived directly in `resource` before profiles existed.
+const PROFILE_FILES: [&str; 3] = ["config.toml", "score.json", "score.db"];
+
+/// A person's settings and history, kept in a directory of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl Profile {
+    pub fn config_file(&self) -> PathBuf {
+        self.dir.join("config.toml")
+    }
+
+    /// The JSON-lines history, also where the SQLite history is kept next to.
+    pub fn history_file(&self) -> PathBuf {
+        self.dir.join("score.json")
+    }
+}
+
+/// The profiles in a directory, one subdirectory each.
+pub struct Profiles {
+    dir: PathBuf,
+}
+
+impl Profiles {
+    /// Opens the profiles in `dir`. When there are none yet, the default profile is made
+    /// out of the config and history files found in `legacy_dir`, which are moved into it.
+    pub fn open(dir: impl AsRef<Path>, legacy_dir: impl AsRef<Path>) -> Result<Self, ErrorKind> {
+        let profiles = Profiles {
+            dir: dir.as_ref().to_path_buf(),
+        };
+        if !profiles.list()?.is_empty() {
+            return Ok(profiles);
+        }
+
+        let profile = profiles.create(DEFAULT_PROFILE)?;
+        for file in PROFILE_FILES {
+            let from = legacy_dir.as_ref().join(file);
+            if from.exists() {
+                std::fs::rename(&from, profile.dir.join(file))
+                    .map_err(|e| ErrorKind::FileError(from, e))?;
+            }
+        }
+        Ok(profiles)
+    }
+
+    /// Profile names, sorted.
+    pub fn list(&self) -> Result<Vec<String>, ErrorKind> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(self.file_error(&self.dir, e)),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| self.file_error(&self.dir, e))?;
+            if entry.path().is_dir() {
+                names.extend(entry.file_name().to_str().map(str::to_string));
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Profile, ErrorKind> {
+        let profile = self.profile(name);
+        match profile.dir.is_dir() {
+            true => Ok(profile),
+            false => Err(ErrorKind::InvalidProfile(format!("{} does not exist", name))),
+        }
+    }
+
+    /// The profile to start with: the one used last, or else the first one.
+    pub fn last(&self) -> Result<Profile, ErrorKind> {
+        let last = std::fs::read_to_string(self.dir.join(LAST_FILE)).unwrap_or_default();
+        match self.get(last.trim()) {
+            Ok(profile) if !last.trim().is_empty() => Ok(profile),
+            _ => match self.list()?.first() {
+                Some(name) => self.get(name),
+                None => self.create(DEFAULT_PROFILE),
+            },
+        }
+    }
+
+    /// Remembers `name` as the profile to start with next time.
+    pub fn set_last(&self, name: &str) -> Result<(), ErrorKind> {
+        let path = self.dir.join(LAST_FILE);
+        std::fs::write(&path, name).map_err(|e| ErrorKind::FileError(path, e))
+    }
+
+    pub fn create(&self, name: &str) -> Result<Profile, ErrorKind> {
+        validate_name(name)?;
+        let profile = self.profile(name);
+        if profile.dir.exists() {
+            return Err(ErrorKind::InvalidProfile(format!("{} already exists", name)));
+        }
+        std::fs::create_dir_all(&profile.dir).map_err(|e| self.file_error(&profile.dir, e))?;
+        Ok(profile)
+    }
+
+    pub fn rename(&self, name: &str, new_name: &str) -> Result<Profile, ErrorKind> {
+        validate_name(new_name)?;
+        let profile = self.get(name)?;
+        let renamed = self.profile(new_name);
+        if renamed.dir.exists() {
+            return Err(ErrorKind::InvalidProfile(format!("{} already exists", new_name)));
+        }
+        std::fs::rename(&profile.dir, &renamed.dir).map_err(|e| self.file_error(&profile.dir, e))?;
+        Ok(renamed)
+    }
+
+    /// Deletes a profile with its history. The last profile left cannot be deleted.
+    pub fn delete(&self, name: &str) -> Result<(), ErrorKind> {
+        let profile = self.get(name)?;
+        if self.list()?.len() <= 1 {
+            return Err(ErrorKind::InvalidProfile(format!("{} is the only profile", name)));
+        }
+        std::fs::remove_dir_all(&profile.dir).map_err(|e| self.file_error(&profile.dir, e))
+    }
+
+    fn profile(&self, name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            dir: self.dir.join(name),
+        }
+    }
+
+    fn file_error(&self, path: &Path, e: std::io::Error) -> ErrorKind {
+        ErrorKind::FileError(path.to_path_buf(), e)
+    }
+}
+
+/// Names become directory names, so they are kept to letters, digits, `-` and `_`.
+pub fn validate_name(name: &str) -> Result<(), ErrorKind> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= 32
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(ErrorKind::InvalidProfile(format!(
+            "{:?} is not a valid name, use up to 32 letters, digits, - and _",
+            name
+        ))),
+    }
+}
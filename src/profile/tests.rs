@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::profile::profile::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_open_moves_legacy_files() {
+        let dir = temp_dir("bunbuntype_test_profile_legacy");
+        std::fs::write(dir.join("config.toml"), "theme = \"nord\"\n").unwrap();
+        std::fs::write(dir.join("score.json"), "").unwrap();
+
+        let profiles = Profiles::open(dir.join("profiles"), &dir).unwrap();
+        assert_eq!(profiles.list().unwrap(), vec![DEFAULT_PROFILE.to_string()]);
+        let profile = profiles.last().unwrap();
+        assert_eq!(profile.name, DEFAULT_PROFILE);
+        assert!(profile.config_file().exists());
+        assert!(profile.history_file().exists());
+        assert!(!dir.join("config.toml").exists());
+
+        // Opening again leaves existing profiles alone
+        std::fs::write(dir.join("config.toml"), "").unwrap();
+        Profiles::open(dir.join("profiles"), &dir).unwrap();
+        assert!(dir.join("config.toml").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_manage_profiles() {
+        let dir = temp_dir("bunbuntype_test_profile_manage");
+        let profiles = Profiles::open(dir.join("profiles"), &dir).unwrap();
+
+        profiles.create("sam").unwrap();
+        assert!(profiles.create("sam").is_err());
+        assert!(profiles.create("../escape").is_err());
+        assert!(profiles.create("").is_err());
+        assert_eq!(profiles.list().unwrap(), vec!["default", "sam"]);
+
+        profiles.set_last("sam").unwrap();
+        assert_eq!(profiles.last().unwrap().name, "sam");
+
+        let renamed = profiles.rename("sam", "alex").unwrap();
+        assert_eq!(renamed.name, "alex");
+        assert!(profiles.get("sam").is_err());
+        assert!(profiles.rename("alex", "default").is_err());
+        // The last used profile is gone, so the first one is used
+        assert_eq!(profiles.last().unwrap().name, "alex");
+
+        profiles.delete("alex").unwrap();
+        assert_eq!(profiles.list().unwrap(), vec!["default"]);
+        assert!(profiles.delete("default").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 mod tests;
 pub mod wpm_results;
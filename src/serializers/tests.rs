@@ -1,35 +1,48 @@
 #[cfg(test)]
 mod tests {
     use crate::serializers::wpm_results::WpmResult;
-    use serde::de::Deserialize;
-    use serde::de::Visitor;
+    use crate::test_util::temp_path;
     use std::fs::File;
     use std::io::{Read, Write};
 
-    static FILE: &str = "resource/score.json";
-
     #[test]
     fn test_serde() {
+        let path = temp_path("bunbuntype_test_serde.json");
         let mut content = String::new();
 
         let new_wpm = WpmResult::new(27.0, 0.95, 1.5, 25.0);
 
         {
-            let mut file = File::options().append(true).open(FILE).unwrap();
+            let mut file = File::create(&path).unwrap();
             file.write_all(new_wpm.to_json().as_bytes()).unwrap();
-            file.sync_data();
+            file.sync_data().unwrap();
         }
 
-        let mut file = File::open(FILE).unwrap();
+        let mut file = File::open(&path).unwrap();
 
-        file.read_to_string(&mut content);
+        file.read_to_string(&mut content).unwrap();
 
         let wpm_results = serde_json::from_str::<WpmResult>(&content).unwrap();
 
-        // let mut wpm_vec: Vec<WpmResult> = WpmResult::from_file(FILE).unwrap();
-        //
-        // println!("{:?}", wpm_vec);
+        let wpm_vec: Vec<WpmResult> = WpmResult::from_file(&path).unwrap();
 
         assert_eq!(wpm_results.wpm, 27.0);
+        assert_eq!(wpm_vec.len(), 1);
+    }
+
+    #[test]
+    fn test_from_file_lossy() {
+        let path = temp_path("bunbuntype_test_lossy.json");
+        let good = WpmResult::new(27.0, 0.95, 1.5, 25.0).to_json();
+        std::fs::write(&path, format!("{}\n\nnot json\n{}\n", good, good)).unwrap();
+
+        let (results, errors) = WpmResult::from_file_lossy(&path).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("bunbuntype_test_lossy.json:3:"));
+        assert!(WpmResult::from_file(&path).is_err());
+
+        let (missing, _) = WpmResult::from_file_lossy(path.with_extension("missing")).unwrap();
+        assert!(missing.is_empty());
     }
 }
@@ -1,19 +1,47 @@
-use std::io::Read;
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::path::Path;
-use chrono::{serde::ts_seconds, DateTime, Local};
-use serde::de::Visitor;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::str::FromStr;
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::Error;
+use crate::engine::engine::{Difficulty, Sample, TestMode};
+use crate::error::ErrorKind;
 use crate::loaders::loader::read_lines;
+use crate::validation::validation::Flag;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct WpmResult {
     pub wpm: f64,
     pub accuracy: f64,
     pub duration: f64,
     pub awpm: f64,
     pub date_time: DateTime<Local>,
+    /// The fields below are missing from results saved by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TestMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    /// The tool an imported result came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Speed over the test, one sample per second.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<Sample>,
+    /// Why the result is not trusted, empty for a valid result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<Flag>,
+    /// Hash over the result and its keystrokes, to notice later edits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+    /// Labels given to the result from the history, not covered by `integrity`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The rules the test was typed under.
+    #[serde(default, skip_serializing_if = "Difficulty::is_default")]
+    pub difficulty: Difficulty,
 }
 
 impl WpmResult {
@@ -24,9 +52,23 @@ impl WpmResult {
             duration,
             awpm,
             date_time: Local::now(),
+            mode: None,
+            language: None,
+            layout: None,
+            source: None,
+            timeline: Vec::new(),
+            flags: Vec::new(),
+            integrity: None,
+            tags: Vec::new(),
+            difficulty: Difficulty::default(),
         }
     }
 
+    /// Whether the result can count as a best, see `Flag`.
+    pub fn is_valid(&self) -> bool {
+        self.flags.is_empty()
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
@@ -35,17 +77,75 @@ impl WpmResult {
         serde_json::from_str(json)
     }
 
-    pub fn from_file(path: impl AsRef<Path>) -> Result<Vec<WpmResult>, Error> {
-        let f = read_lines(path).unwrap();
-        let mut vec: Vec<WpmResult> = Vec::new();
-        let num = f.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+    /// Appends the result as a new line of the JSON-lines file at `path`.
+    pub fn append_to_file(&self, path: impl AsRef<Path>) -> Result<(), ErrorKind> {
+        let path = path.as_ref();
+        let file_error = |e| ErrorKind::FileError(path.to_path_buf(), e);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(file_error)?;
+        writeln!(file, "{}", self.to_json()).map_err(file_error)?;
+        Ok(())
+    }
 
-        for mut i in num {
-            if !i.is_empty() {
-                vec.push(serde_json::from_str(&i.as_str()).unwrap());
-            }
+    /// Reads every result in the JSON-lines file at `path`, failing on the first bad line.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Vec<WpmResult>, ErrorKind> {
+        let (results, mut errors) = WpmResult::from_file_lossy(path)?;
+        match errors.is_empty() {
+            true => Ok(results),
+            false => Err(errors.remove(0)),
         }
+    }
 
-        Ok(vec)
+    /// Reads the JSON-lines file at `path`, skipping lines that are not valid results and
+    /// returning an error for each of them. A missing file is an empty history.
+    pub fn from_file_lossy(
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<WpmResult>, Vec<ErrorKind>), ErrorKind> {
+        read_json_lines(path)
     }
 }
+
+/// Reads one `T` per line of the JSON-lines file at `path`, like `WpmResult::from_file_lossy`.
+pub fn read_json_lines<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> Result<(Vec<T>, Vec<ErrorKind>), ErrorKind> {
+    let (vec, failed) = read_json_lines_raw(path)?;
+    Ok((vec, failed.into_iter().map(|(_, e)| e).collect()))
+}
+
+/// A line that could not be read, along with why.
+pub type FailedLine = (String, ErrorKind);
+
+/// Like `read_json_lines`, with the text of each line that could not be read next to its
+/// error so it can be written back as it was.
+pub fn read_json_lines_raw<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> Result<(Vec<T>, Vec<FailedLine>), ErrorKind> {
+    let path = path.as_ref();
+    let lines = match read_lines(path) {
+        Ok(lines) => lines,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(ErrorKind::FileError(path.to_path_buf(), e)),
+    };
+
+    let mut vec = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(value) => vec.push(value),
+            Err(e) => errors.push((
+                line.clone(),
+                ErrorKind::HistoryError(path.to_path_buf(), i + 1, Box::new(ErrorKind::from(e))),
+            )),
+        }
+    }
+
+    Ok((vec, errors))
+}
@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+mod tests;
+#[cfg(feature = "audio")]
+pub mod rodio_sink;
+#[allow(clippy::module_inception)]
+pub mod sound;
@@ -0,0 +1,35 @@
+use std::io::Cursor;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
+use crate::error::ErrorKind;
+use crate::sound::sound::{SoundEvent, SoundSink};
+
+/// The default audio output of the machine. Sounds are mixed, so fast typing overlaps
+/// clicks instead of queueing them.
+pub struct RodioSink {
+    // Playing stops once the stream is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+impl RodioSink {
+    pub fn open() -> Result<Self, ErrorKind> {
+        let (stream, handle) =
+            OutputStream::try_default().map_err(|e| ErrorKind::AudioError(e.to_string()))?;
+        Ok(RodioSink {
+            _stream: stream,
+            handle,
+        })
+    }
+}
+
+impl SoundSink for RodioSink {
+    fn play(&mut self, _event: SoundEvent, wav: &[u8], volume: f32) -> bool {
+        let source = match Decoder::new(Cursor::new(wav.to_vec())) {
+            Ok(source) => source,
+            Err(_) => return false,
+        };
+        self.handle
+            .play_raw(source.amplify(volume).convert_samples())
+            .is_ok()
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::ErrorKind;
+
+pub const SOUNDS_DIR: &str = "resource/sounds";
+pub const DEFAULT_SOUND_PACK: &str = "default";
+/// Volume steps offered by the settings, in percent.
+pub const VOLUMES: [u8; 5] = [0, 25, 50, 75, 100];
+
+/// Which typing sounds play.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Sounds {
+    #[default]
+    Off,
+    /// Only mistakes make a sound.
+    Errors,
+    /// Every key clicks, mistakes sound different.
+    All,
+}
+
+impl Sounds {
+    pub const ALL: [Sounds; 3] = [Sounds::Off, Sounds::Errors, Sounds::All];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sounds::Off => "off",
+            Sounds::Errors => "errors",
+            Sounds::All => "all",
+        }
+    }
+
+    pub fn plays(&self, event: SoundEvent) -> bool {
+        match self {
+            Sounds::Off => false,
+            Sounds::Errors => event == SoundEvent::Error,
+            Sounds::All => true,
+        }
+    }
+}
+
+/// Something that happened while typing that can make a sound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundEvent {
+    /// A key typed right, or a backspace.
+    Key,
+    /// A key typed wrong.
+    Error,
+}
+
+impl SoundEvent {
+    /// The file a sound pack keeps the sound in.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SoundEvent::Key => "key.wav",
+            SoundEvent::Error => "error.wav",
+        }
+    }
+}
+
+/// A directory of WAV files, one per event. Events without a file make no sound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundPack {
+    pub name: String,
+    pub key: Option<Vec<u8>>,
+    pub error: Option<Vec<u8>>,
+}
+
+impl SoundPack {
+    /// Every usable pack among the directories in `dir`, along with an error for each that
+    /// could not be used. A missing `dir` has no packs.
+    pub fn load_all(dir: impl AsRef<Path>) -> (Vec<SoundPack>, Vec<ErrorKind>) {
+        let mut paths = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        let mut packs = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            match SoundPack::from_dir(&path) {
+                Ok(pack) => packs.push(pack),
+                Err(e) => errors.push(e),
+            }
+        }
+        (packs, errors)
+    }
+
+    /// Reads the pack in `dir`, named after the directory.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<SoundPack, ErrorKind> {
+        let dir = dir.as_ref();
+        let sound_error = |e: ErrorKind| ErrorKind::SoundError(dir.to_path_buf(), Box::new(e));
+        let read = |event: SoundEvent| -> Result<Option<Vec<u8>>, ErrorKind> {
+            let path = dir.join(event.file_name());
+            match std::fs::read(&path) {
+                Ok(bytes) => check_wav(&bytes)
+                    .map(|()| Some(bytes))
+                    .map_err(|e| ErrorKind::SoundError(path, Box::new(e))),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(ErrorKind::FileError(path, e)),
+            }
+        };
+
+        let pack = SoundPack {
+            name: dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            key: read(SoundEvent::Key)?,
+            error: read(SoundEvent::Error)?,
+        };
+        if pack.key.is_none() && pack.error.is_none() {
+            return Err(sound_error(ErrorKind::InvalidSound(format!(
+                "needs {} or {}",
+                SoundEvent::Key.file_name(),
+                SoundEvent::Error.file_name()
+            ))));
+        }
+        Ok(pack)
+    }
+
+    pub fn sound(&self, event: SoundEvent) -> Option<&[u8]> {
+        match event {
+            SoundEvent::Key => self.key.as_deref(),
+            SoundEvent::Error => self.error.as_deref(),
+        }
+    }
+}
+
+/// Checks for the RIFF header of a WAV file, so a broken file is reported when its pack is
+/// loaded instead of quietly never playing.
+fn check_wav(bytes: &[u8]) -> Result<(), ErrorKind> {
+    match bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        true => Ok(()),
+        false => Err(ErrorKind::InvalidSound("not a WAV file".to_string())),
+    }
+}
+
+/// Picks `name` from `packs`, falling back to the first one when `name` is unknown.
+pub fn select_pack<'a>(packs: &'a [SoundPack], name: &str) -> Option<&'a SoundPack> {
+    packs.iter().find(|p| p.name == name).or(packs.first())
+}
+
+/// Where sounds are played.
+pub trait SoundSink {
+    /// Starts playing `wav` for `event` at `volume`, from 0 to 1, without waiting for it to
+    /// end. Returns false when it could not be played.
+    fn play(&mut self, event: SoundEvent, wav: &[u8], volume: f32) -> bool;
+}
+
+/// Plays nothing, for builds without the `audio` feature and machines without audio. It
+/// keeps count of what it was given.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NullSink {
+    pub played: usize,
+    pub last: Option<(SoundEvent, f32)>,
+}
+
+impl SoundSink for NullSink {
+    fn play(&mut self, event: SoundEvent, _wav: &[u8], volume: f32) -> bool {
+        self.played += 1;
+        self.last = Some((event, volume));
+        false
+    }
+}
+
+/// Plays the sound for `event` through `sink` if `sounds` asks for it, at `volume` percent.
+/// Returns true when the terminal bell should ring instead, which is when a mistake should
+/// sound but could not be played.
+pub fn dispatch(
+    event: SoundEvent,
+    sounds: Sounds,
+    volume: u8,
+    pack: Option<&SoundPack>,
+    sink: &mut dyn SoundSink,
+) -> bool {
+    if !sounds.plays(event) || volume == 0 {
+        return false;
+    }
+    let volume = volume.min(100) as f32 / 100.0;
+    let played = pack
+        .and_then(|pack| pack.sound(event))
+        .is_some_and(|wav| sink.play(event, wav, volume));
+    !played && event == SoundEvent::Error
+}
+
+/// The audio output of this machine, or a `NullSink` when this build or machine has none.
+pub fn open_sink() -> Result<Box<dyn SoundSink>, ErrorKind> {
+    #[cfg(feature = "audio")]
+    {
+        use crate::sound::rodio_sink::RodioSink;
+        Ok(Box::new(RodioSink::open()?))
+    }
+    #[cfg(not(feature = "audio"))]
+    Ok(Box::new(NullSink::default()))
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::sound::sound::*;
+    use crate::test_util::temp_dir;
+
+    fn pack(key: bool, error: bool) -> SoundPack {
+        let wav = b"RIFF\0\0\0\0WAVEfmt ".to_vec();
+        SoundPack {
+            name: "test".to_string(),
+            key: key.then(|| wav.clone()),
+            error: error.then_some(wav),
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let full = pack(true, true);
+        let mut sink = NullSink::default();
+
+        // Nothing reaches the sink while sounds are off or muted
+        assert!(!dispatch(SoundEvent::Error, Sounds::Off, 50, Some(&full), &mut sink));
+        assert!(!dispatch(SoundEvent::Error, Sounds::All, 0, Some(&full), &mut sink));
+        assert_eq!(sink.played, 0);
+
+        // Only mistakes sound with `errors`, and the bell rings as the null sink plays nothing
+        assert!(!dispatch(SoundEvent::Key, Sounds::Errors, 50, Some(&full), &mut sink));
+        assert_eq!(sink.played, 0);
+        assert!(dispatch(SoundEvent::Error, Sounds::Errors, 50, Some(&full), &mut sink));
+        assert_eq!(sink.last, Some((SoundEvent::Error, 0.5)));
+
+        // Clicks never fall back to the bell
+        assert!(!dispatch(SoundEvent::Key, Sounds::All, 100, Some(&full), &mut sink));
+        assert_eq!(sink.played, 2);
+        assert_eq!(sink.last, Some((SoundEvent::Key, 1.0)));
+
+        // A pack without the sound leaves the sink alone
+        let clicks = pack(true, false);
+        assert!(dispatch(SoundEvent::Error, Sounds::All, 50, Some(&clicks), &mut sink));
+        assert!(dispatch(SoundEvent::Error, Sounds::All, 50, None, &mut sink));
+        assert_eq!(sink.played, 2);
+    }
+
+    #[test]
+    fn test_playing_sink() {
+        struct Speaker(Vec<SoundEvent>);
+        impl SoundSink for Speaker {
+            fn play(&mut self, event: SoundEvent, _wav: &[u8], _volume: f32) -> bool {
+                self.0.push(event);
+                true
+            }
+        }
+
+        let mut speaker = Speaker(Vec::new());
+        let full = pack(true, true);
+        assert!(!dispatch(SoundEvent::Error, Sounds::All, 50, Some(&full), &mut speaker));
+        assert!(!dispatch(SoundEvent::Key, Sounds::All, 50, Some(&full), &mut speaker));
+        assert_eq!(speaker.0, vec![SoundEvent::Error, SoundEvent::Key]);
+    }
+
+    #[test]
+    fn test_sound_packs() {
+        let dir = temp_dir("bunbuntype_test_sounds");
+        std::fs::create_dir(dir.join("clicky")).unwrap();
+        std::fs::write(dir.join("clicky/key.wav"), b"RIFF\0\0\0\0WAVEfmt ").unwrap();
+        std::fs::create_dir(dir.join("broken")).unwrap();
+        std::fs::write(dir.join("broken/error.wav"), b"not a wav file").unwrap();
+        std::fs::create_dir(dir.join("empty")).unwrap();
+
+        let (packs, errors) = SoundPack::load_all(&dir);
+        assert_eq!(packs.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert!(packs[0].sound(SoundEvent::Key).is_some());
+        assert!(packs[0].sound(SoundEvent::Error).is_none());
+
+        assert_eq!(select_pack(&packs, "missing").unwrap().name, "clicky");
+        assert!(select_pack(&[], DEFAULT_SOUND_PACK).is_none());
+    }
+
+    #[test]
+    fn test_bundled_pack() {
+        let (packs, errors) = SoundPack::load_all(SOUNDS_DIR);
+        assert!(errors.is_empty());
+        let pack = select_pack(&packs, DEFAULT_SOUND_PACK).unwrap();
+        assert_eq!(pack.name, DEFAULT_SOUND_PACK);
+        assert!(pack.key.is_some() && pack.error.is_some());
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use chrono::Datelike;
+use crate::serializers::wpm_results::WpmResult;
+
+/// Count, best and average of a group of results.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Summary {
+    pub tests: usize,
+    /// Best WPM of the valid results.
+    pub best_wpm: f64,
+    pub total_wpm: f64,
+    pub total_accuracy: f64,
+    /// Minutes spent typing.
+    pub total_duration: f64,
+}
+
+impl Summary {
+    pub fn add(&mut self, result: &WpmResult) {
+        self.tests += 1;
+        if result.is_valid() {
+            self.best_wpm = self.best_wpm.max(result.wpm);
+        }
+        self.total_wpm += result.wpm;
+        self.total_accuracy += result.accuracy;
+        self.total_duration += result.duration;
+    }
+
+    pub fn average_wpm(&self) -> f64 {
+        self.total_wpm / self.tests as f64
+    }
+
+    pub fn average_accuracy(&self) -> f64 {
+        self.total_accuracy / self.tests as f64
+    }
+}
+
+/// Results grouped by test mode, `unknown` for results saved without one.
+pub fn by_mode(results: &[WpmResult]) -> BTreeMap<String, Summary> {
+    let mut by_mode = BTreeMap::<String, Summary>::new();
+    for result in results {
+        let mode = result.mode.map(|m| m.to_string()).unwrap_or_else(|| "unknown".to_string());
+        by_mode.entry(mode).or_default().add(result);
+    }
+    by_mode
+}
+
+/// Results grouped by ISO week, as `(year, week)`.
+pub fn by_week(results: &[WpmResult]) -> BTreeMap<(i32, u32), Summary> {
+    let mut by_week = BTreeMap::<(i32, u32), Summary>::new();
+    for result in results {
+        let week = result.date_time.iso_week();
+        by_week.entry((week.year(), week.week())).or_default().add(result);
+    }
+    by_week
+}
+
+/// Aggregates of a history shown on the statistics dashboard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub total: Summary,
+    pub by_mode: BTreeMap<String, Summary>,
+    pub by_week: BTreeMap<(i32, u32), Summary>,
+    /// The most recent result.
+    pub last: Option<WpmResult>,
+    /// Share of results slower than the last one, from 0 to 100.
+    pub last_percentile: Option<f64>,
+    /// Change in WPM per week, fitted over every result.
+    pub weekly_improvement: Option<f64>,
+    wpms: Vec<f64>,
+}
+
+impl Stats {
+    /// Computes the stats of `results`, which are in date order.
+    pub fn new(results: &[WpmResult]) -> Self {
+        let mut total = Summary::default();
+        for result in results {
+            total.add(result);
+        }
+        let last = results.last().cloned();
+        let last_percentile = last.as_ref().map(|last| {
+            let slower = results.iter().filter(|r| r.wpm < last.wpm).count();
+            slower as f64 / results.len() as f64 * 100.0
+        });
+
+        Stats {
+            total,
+            by_mode: by_mode(results),
+            by_week: by_week(results),
+            last,
+            last_percentile,
+            weekly_improvement: weekly_improvement(results),
+            wpms: results.iter().map(|r| r.wpm).collect(),
+        }
+    }
+
+    /// How many results fall in each `width` WPM wide bucket, from the slowest bucket to
+    /// the fastest, as `(lowest WPM of the bucket, count)`.
+    pub fn histogram(&self, width: u64) -> Vec<(u64, u64)> {
+        let width = width.max(1);
+        let bucket = |wpm: f64| wpm.max(0.0) as u64 / width;
+        let (min, max) = match (
+            self.wpms.iter().copied().map(bucket).min(),
+            self.wpms.iter().copied().map(bucket).max(),
+        ) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return Vec::new(),
+        };
+
+        let mut counts = vec![0; (max - min + 1) as usize];
+        for wpm in &self.wpms {
+            counts[(bucket(*wpm) - min) as usize] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| ((min + i as u64) * width, count))
+            .collect()
+    }
+}
+
+/// The slope of a least squares line through WPM over time, in WPM per week. Needs results
+/// taken at two different moments at least.
+fn weekly_improvement(results: &[WpmResult]) -> Option<f64> {
+    let first = results.first()?.date_time;
+    let points = results
+        .iter()
+        .map(|r| ((r.date_time - first).num_seconds() as f64 / (7.0 * 86_400.0), r.wpm))
+        .collect::<Vec<_>>();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+    let variance = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+    match variance > 0.0 {
+        true => Some(covariance / variance),
+        false => None,
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use crate::engine::engine::TestMode;
+    use crate::serializers::wpm_results::WpmResult;
+    use crate::stats::stats::*;
+
+    fn result(day: u32, wpm: f64, mode: Option<TestMode>) -> WpmResult {
+        let mut result = WpmResult::new(wpm, 0.9, 0.5, wpm);
+        result.date_time = Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        result.mode = mode;
+        result
+    }
+
+    #[test]
+    fn test_stats() {
+        let time = Some(TestMode::Time(30));
+        let stats = Stats::new(&[
+            result(4, 60.0, time),
+            result(11, 72.0, time),
+            result(18, 81.0, None),
+            result(18, 70.0, None),
+        ]);
+
+        assert_eq!(stats.total.tests, 4);
+        assert_eq!(stats.total.total_duration, 2.0);
+        assert_eq!(stats.total.best_wpm, 81.0);
+        assert_eq!(stats.by_mode["time 30"].average_wpm(), 66.0);
+        assert_eq!(stats.by_mode["unknown"].tests, 2);
+        assert_eq!(stats.by_week.len(), 3);
+        // Only the first test was slower than the last one
+        assert_eq!(stats.last_percentile, Some(25.0));
+        assert!(stats.weekly_improvement.unwrap() > 5.0);
+
+        assert_eq!(stats.histogram(10), vec![(60, 1), (70, 2), (80, 1)]);
+        assert_eq!(stats.histogram(25), vec![(50, 3), (75, 1)]);
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = Stats::new(&[]);
+
+        assert_eq!(stats.total.tests, 0);
+        assert_eq!(stats.last_percentile, None);
+        assert_eq!(stats.weekly_improvement, None);
+        assert!(stats.histogram(10).is_empty());
+        assert_eq!(Stats::new(&[result(4, 60.0, None)]).weekly_improvement, None);
+    }
+}
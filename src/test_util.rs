@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+
+/// A path under the system temp dir for `name`, unique to this test run, with whatever an
+/// earlier run left there removed.
+pub fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// A fresh, empty directory under the system temp dir for `name`, unique to this test run.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+mod tests;
+#[allow(clippy::module_inception)]
+pub mod theme;
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::theme::theme::*;
+    use tui::style::Color;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse_color("light_green").unwrap(), Color::LightGreen);
+        assert_eq!(parse_color("Dark Gray").unwrap(), Color::DarkGray);
+        assert_eq!(parse_color("42").unwrap(), Color::Indexed(42));
+        assert!(parse_color("#fff").is_err());
+        assert!(parse_color("mauve").is_err());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let theme = Theme::from_toml("text = \"#101010\"\ncaret = \"red\"", "mine").unwrap();
+        let base = Theme::default();
+
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.text, Color::Rgb(16, 16, 16));
+        assert_eq!(theme.caret, Color::Red);
+        assert_eq!(theme.correct, base.correct);
+        assert!(Theme::from_toml("text = \"nope\"", "bad").is_err());
+    }
+
+    #[test]
+    fn test_bundled() {
+        let themes = Theme::bundled();
+        let names = themes.iter().map(|t| t.name.as_str()).collect::<HashSet<_>>();
+
+        assert_eq!(names.len(), themes.len());
+        assert!(themes.iter().any(|t| t.name == "high_contrast"));
+        assert!(!themes.iter().find(|t| t.name == FALLBACK_THEME).unwrap().is_truecolor());
+    }
+
+    #[test]
+    fn test_select_theme() {
+        let themes = Theme::bundled();
+
+        assert_eq!(select_theme(&themes, "nord", true).name, "nord");
+        assert_eq!(select_theme(&themes, "nord", false).name, FALLBACK_THEME);
+        assert_eq!(select_theme(&themes, "missing", true).name, DEFAULT_THEME);
+    }
+}
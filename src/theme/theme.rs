@@ -0,0 +1,273 @@
+use std::path::Path;
+use serde::Deserialize;
+use tui::style::Color;
+use tui::style::Color::Rgb;
+use crate::error::ErrorKind;
+
+pub const THEMES_DIR: &str = "resource/themes";
+pub const DEFAULT_THEME: &str = "bunbun";
+pub const FALLBACK_THEME: &str = "basic16";
+
+/// Colors used by the UI, named by the role they play rather than by their value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub text: Color,
+    pub sub: Color,
+    pub correct: Color,
+    pub incorrect: Color,
+    pub extra: Color,
+    pub caret: Color,
+    pub accent: Color,
+    pub chart_bar: Color,
+    pub chart_value: Color,
+}
+
+/// A theme as written in a user TOML file. Missing roles are taken from the default theme.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ThemeFile {
+    name: Option<String>,
+    background: Option<String>,
+    text: Option<String>,
+    sub: Option<String>,
+    correct: Option<String>,
+    incorrect: Option<String>,
+    extra: Option<String>,
+    caret: Option<String>,
+    accent: Option<String>,
+    chart_bar: Option<String>,
+    chart_value: Option<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_THEME.to_string(),
+            background: Rgb(0, 0, 0),
+            text: Rgb(255, 255, 255),
+            sub: Rgb(128, 128, 128),
+            correct: Rgb(144, 238, 144),
+            incorrect: Rgb(255, 0, 0),
+            extra: Rgb(139, 0, 0),
+            caret: Rgb(255, 215, 0),
+            accent: Rgb(144, 238, 144),
+            chart_bar: Rgb(255, 255, 0),
+            chart_value: Rgb(0, 0, 0),
+        }
+    }
+}
+
+impl Theme {
+    /// Themes shipped with the binary.
+    pub fn bundled() -> Vec<Theme> {
+        vec![
+            Theme::default(),
+            Theme {
+                name: "high_contrast".to_string(),
+                background: Rgb(0, 0, 0),
+                text: Rgb(255, 255, 255),
+                sub: Rgb(192, 192, 192),
+                correct: Rgb(0, 255, 0),
+                incorrect: Rgb(255, 0, 0),
+                extra: Rgb(255, 0, 255),
+                caret: Rgb(255, 255, 0),
+                accent: Rgb(0, 255, 255),
+                chart_bar: Rgb(255, 255, 0),
+                chart_value: Rgb(0, 0, 0),
+            },
+            Theme {
+                name: "nord".to_string(),
+                background: Rgb(46, 52, 64),
+                text: Rgb(236, 239, 244),
+                sub: Rgb(76, 86, 106),
+                correct: Rgb(163, 190, 140),
+                incorrect: Rgb(191, 97, 106),
+                extra: Rgb(208, 135, 112),
+                caret: Rgb(235, 203, 139),
+                accent: Rgb(136, 192, 208),
+                chart_bar: Rgb(129, 161, 193),
+                chart_value: Rgb(46, 52, 64),
+            },
+            Theme {
+                name: "solarized_dark".to_string(),
+                background: Rgb(0, 43, 54),
+                text: Rgb(147, 161, 161),
+                sub: Rgb(88, 110, 117),
+                correct: Rgb(133, 153, 0),
+                incorrect: Rgb(220, 50, 47),
+                extra: Rgb(203, 75, 22),
+                caret: Rgb(181, 137, 0),
+                accent: Rgb(38, 139, 210),
+                chart_bar: Rgb(42, 161, 152),
+                chart_value: Rgb(0, 43, 54),
+            },
+            Theme {
+                name: FALLBACK_THEME.to_string(),
+                background: Color::Black,
+                text: Color::White,
+                sub: Color::DarkGray,
+                correct: Color::Green,
+                incorrect: Color::Red,
+                extra: Color::Magenta,
+                caret: Color::Yellow,
+                accent: Color::LightGreen,
+                chart_bar: Color::Yellow,
+                chart_value: Color::Black,
+            },
+        ]
+    }
+
+    /// Bundled themes followed by every valid `*.toml` theme in `dir`, along with an error
+    /// for each theme file that could not be used.
+    /// A user theme with the same name as a bundled one replaces it.
+    pub fn load_all(dir: impl AsRef<Path>) -> (Vec<Theme>, Vec<ErrorKind>) {
+        let mut themes = Theme::bundled();
+
+        let mut paths = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            match Theme::from_file(&path) {
+                Ok(theme) => match themes.iter_mut().find(|t| t.name == theme.name) {
+                    Some(existing) => *existing = theme,
+                    None => themes.push(theme),
+                },
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (themes, errors)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Theme, ErrorKind> {
+        let path = path.as_ref();
+        let theme_error = |e: ErrorKind| ErrorKind::ThemeError(path.to_path_buf(), Box::new(e));
+        let content = std::fs::read_to_string(path).map_err(|e| theme_error(e.into()))?;
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Theme::from_toml(&content, &stem).map_err(theme_error)
+    }
+
+    /// Parses a theme from TOML, naming it `default_name` if the file has no `name` key.
+    pub fn from_toml(content: &str, default_name: &str) -> Result<Theme, ErrorKind> {
+        let file: ThemeFile = toml::from_str(content)?;
+        let base = Theme::default();
+
+        let pick = |value: Option<String>, fallback: Color| -> Result<Color, ErrorKind> {
+            match value {
+                Some(v) => parse_color(&v),
+                None => Ok(fallback),
+            }
+        };
+
+        Ok(Theme {
+            name: file.name.unwrap_or_else(|| default_name.to_string()),
+            background: pick(file.background, base.background)?,
+            text: pick(file.text, base.text)?,
+            sub: pick(file.sub, base.sub)?,
+            correct: pick(file.correct, base.correct)?,
+            incorrect: pick(file.incorrect, base.incorrect)?,
+            extra: pick(file.extra, base.extra)?,
+            caret: pick(file.caret, base.caret)?,
+            accent: pick(file.accent, base.accent)?,
+            chart_bar: pick(file.chart_bar, base.chart_bar)?,
+            chart_value: pick(file.chart_value, base.chart_value)?,
+        })
+    }
+
+    /// Whether any role needs a truecolor terminal to display correctly.
+    pub fn is_truecolor(&self) -> bool {
+        [
+            self.background,
+            self.text,
+            self.sub,
+            self.correct,
+            self.incorrect,
+            self.extra,
+            self.caret,
+            self.accent,
+            self.chart_bar,
+            self.chart_value,
+        ]
+        .iter()
+        .any(|c| matches!(c, Rgb(..)))
+    }
+}
+
+/// Picks `name` from `themes`, falling back to the 16 color theme when the terminal cannot
+/// show a truecolor one, and to the first theme when `name` is unknown.
+pub fn select_theme(themes: &[Theme], name: &str, truecolor: bool) -> Theme {
+    let wanted = themes
+        .iter()
+        .find(|t| t.name == name)
+        .unwrap_or(&themes[0]);
+
+    if !truecolor && wanted.is_truecolor() {
+        if let Some(fallback) = themes.iter().find(|t| t.name == FALLBACK_THEME) {
+            return fallback.clone();
+        }
+    }
+
+    wanted.clone()
+}
+
+/// Terminals advertise 24-bit color support through `COLORTERM`.
+pub fn supports_truecolor() -> bool {
+    match std::env::var("COLORTERM") {
+        Ok(v) => v == "truecolor" || v == "24bit",
+        Err(_) => false,
+    }
+}
+
+/// Parses `#rrggbb`, a 0-255 palette index or one of the 16 named terminal colors.
+pub fn parse_color(value: &str) -> Result<Color, ErrorKind> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() == 6 {
+            if let Ok(rgb) = u32::from_str_radix(hex, 16) {
+                return Ok(Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+            }
+        }
+        return Err(ErrorKind::InvalidColor(value.to_string()));
+    }
+
+    if let Ok(index) = value.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match value.to_lowercase().replace(['-', ' '], "_").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(ErrorKind::InvalidColor(value.to_string())),
+    };
+
+    Ok(color)
+}
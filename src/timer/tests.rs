@@ -1 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::timer::timer::{TimerView, TimerViewState};
+    use chrono::{Duration, Local};
 
+    #[test]
+    fn test_config_input() {
+        let mut timer = TimerView::new();
+        timer.focus_prev();
+        assert_eq!(timer.focus(), 2);
+
+        timer.input_digit(4);
+        timer.input_digit(5);
+        assert_eq!(timer.render(), "00:00:45");
+
+        // A third digit starts the field over, values are capped
+        timer.input_digit(9);
+        timer.input_digit(9);
+        assert_eq!(timer.duration(), Duration::seconds(59));
+
+        timer.focus_next();
+        timer.input_digit(1);
+        timer.input_digit(2);
+        assert_eq!(timer.duration(), Duration::hours(12) + Duration::seconds(59));
+    }
+
+    #[test]
+    fn test_step_wraps() {
+        let mut timer = TimerView::new();
+        timer.decrement();
+        assert_eq!(timer.render(), "99:00:00");
+
+        timer.set_duration(Duration::minutes(59));
+        timer.focus_next();
+        timer.increment();
+        assert_eq!(timer.render(), "00:00:00");
+    }
+
+    #[test]
+    fn test_countdown() {
+        let start = Local::now();
+        let mut timer = TimerView::new();
+        timer.set_duration(Duration::seconds(10));
+        timer.start_at(start);
+
+        assert!(!timer.update_at(start + Duration::seconds(3)));
+        assert_eq!(timer.remaining(), Duration::seconds(7));
+        assert_eq!(timer.render(), "00:00:07");
+
+        timer.pause_or_resume_at(start + Duration::seconds(4));
+        assert_eq!(timer.state(), TimerViewState::Paused);
+        assert_eq!(timer.remaining(), Duration::seconds(6));
+
+        timer.pause_or_resume_at(start + Duration::seconds(100));
+        assert!(!timer.update_at(start + Duration::seconds(105)));
+        assert_eq!(timer.remaining(), Duration::seconds(1));
+
+        assert!(timer.update_at(start + Duration::seconds(106)));
+        assert_eq!(timer.state(), TimerViewState::Finished);
+        assert_eq!(timer.render(), "Finished!");
+
+        timer.reset();
+        assert_eq!(timer.state(), TimerViewState::Config);
+        assert_eq!(timer.duration(), Duration::seconds(10));
+    }
+
+    #[test]
+    fn test_follow() {
+        let mut timer = TimerView::new();
+        timer.set_duration(Duration::seconds(30));
+
+        timer.follow(Duration::seconds(12), true);
+        assert_eq!(timer.state(), TimerViewState::Running);
+        assert_eq!(timer.render(), "00:00:12");
+
+        timer.follow(Duration::seconds(11), false);
+        assert_eq!(timer.state(), TimerViewState::Paused);
+        assert_eq!(timer.remaining(), Duration::seconds(11));
+        assert_eq!(timer.duration(), Duration::seconds(30));
+    }
+}
@@ -1,12 +1,12 @@
-
-use clock_core::timer::{Timer, TimerData};
-use chrono::Duration;
+use clock_core::timer::Timer;
+use chrono::{DateTime, Duration, Local};
 use hhmmss::Hhmmss;
 
-#[derive(Copy, Clone)]
-enum TimerViewState {
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimerViewState {
     Config,
     Running,
+    Paused,
     Finished,
 }
 
@@ -25,6 +25,12 @@ pub struct TimerView {
     config: TimerViewConfig,
 }
 
+impl Default for TimerView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TimerView {
     pub fn new() -> Self {
         Self {
@@ -41,48 +47,183 @@ impl TimerView {
         }
     }
 
+    pub fn state(&self) -> TimerViewState {
+        self.state
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// The configured duration.
+    pub fn duration(&self) -> Duration {
+        Duration::seconds(
+            self.config.h as i64 * 3600 + self.config.m as i64 * 60 + self.config.s as i64,
+        )
+    }
+
+    /// Configures the timer for `duration` and goes back to the config state.
+    pub fn set_duration(&mut self, duration: Duration) {
+        let seconds = duration.num_seconds().clamp(0, 99 * 3600 + 59 * 60 + 59);
+        self.config.h = (seconds / 3600) as u8;
+        self.config.m = (seconds % 3600 / 60) as u8;
+        self.config.s = (seconds % 60) as u8;
+        self.reset();
+    }
+
+    /// Stops the countdown and goes back to editing the configured duration.
+    pub fn reset(&mut self) {
+        self.timer = Timer::new(self.duration());
+        self.remaining = self.duration();
+        self.state = TimerViewState::Config;
+        self.config.input_buffer.clear();
+    }
+
     pub fn start(&mut self) {
-        let seconds = self.config.h as i64 * 3600
-            + self.config.m as i64 * 60
-            + self.config.s as i64;
-        self.timer = Timer::new(Duration::seconds(seconds));
+        self.start_at(Local::now());
+    }
+
+    pub fn start_at(&mut self, moment: DateTime<Local>) {
+        self.config.input_buffer.clear();
+        self.timer = Timer::new(self.duration());
+        self.remaining = self.duration();
         self.state = TimerViewState::Running;
-        self.timer.pause_or_resume()
+        self.timer.resume_at(moment);
+    }
+
+    pub fn pause_or_resume(&mut self) {
+        self.pause_or_resume_at(Local::now());
+    }
+
+    pub fn pause_or_resume_at(&mut self, moment: DateTime<Local>) {
+        match self.state {
+            TimerViewState::Running => {
+                self.timer.pause_at(moment);
+                self.remaining = self.timer.data.remaining;
+                self.state = TimerViewState::Paused;
+            }
+            TimerViewState::Paused => {
+                self.timer.resume_at(moment);
+                self.state = TimerViewState::Running;
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows `remaining` from a clock kept elsewhere, such as a time test's, in place of
+    /// running its own.
+    pub fn follow(&mut self, remaining: Duration, running: bool) {
+        self.remaining = remaining;
+        self.state = match running {
+            true => TimerViewState::Running,
+            false => TimerViewState::Paused,
+        };
     }
 
-    pub fn update(&mut self) {
+    /// Ends the countdown early, e.g. when the test it belongs to is over.
+    pub fn finish(&mut self) {
+        self.remaining = Duration::zero();
+        self.state = TimerViewState::Finished;
+    }
+
+    /// Field being edited in the config state: 0 for hours, 1 for minutes, 2 for seconds.
+    pub fn focus(&self) -> u8 {
+        self.config.focus
+    }
+
+    pub fn focus_next(&mut self) {
+        self.config.focus = (self.config.focus + 1) % 3;
+        self.config.input_buffer.clear();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.config.focus = (self.config.focus + 2) % 3;
+        self.config.input_buffer.clear();
+    }
+
+    /// Types a digit into the focused field. Two digits fill it, a third starts over.
+    pub fn input_digit(&mut self, digit: u8) {
+        if self.state != TimerViewState::Config || digit > 9 {
+            return;
+        }
+        if self.config.input_buffer.len() >= 2 {
+            self.config.input_buffer.clear();
+        }
+        self.config.input_buffer.push(digit);
+        self.update();
+    }
+
+    pub fn increment(&mut self) {
+        self.step(1);
+    }
+
+    pub fn decrement(&mut self) {
+        self.step(-1);
+    }
+
+    fn step(&mut self, by: i16) {
+        if self.state != TimerViewState::Config {
+            return;
+        }
+        self.config.input_buffer.clear();
+        let (value, max) = self.focused_field();
+        *value = (*value as i16 + by).rem_euclid(max as i16 + 1) as u8;
+        self.remaining = self.duration();
+    }
+
+    fn focused_field(&mut self) -> (&mut u8, u8) {
+        match self.config.focus {
+            0 => (&mut self.config.h, 99),
+            1 => (&mut self.config.m, 59),
+            _ => (&mut self.config.s, 59),
+        }
+    }
+
+    pub fn update(&mut self) -> bool {
+        self.update_at(Local::now())
+    }
+
+    /// Applies pending config input, or refreshes the remaining time from the underlying
+    /// timer. Returns true when the countdown finished during this update.
+    pub fn update_at(&mut self, moment: DateTime<Local>) -> bool {
         match self.state {
             TimerViewState::Config => {
-                if self.config.input_buffer.len() > 0 {
-                    let input = self.config.input_buffer.pop().unwrap();
-                    match self.config.focus {
-                        0 => self.config.h = input,
-                        1 => self.config.m = input,
-                        2 => self.config.s = input,
-                        _ => {}
-                    }
+                if !self.config.input_buffer.is_empty() {
+                    let input = self
+                        .config
+                        .input_buffer
+                        .iter()
+                        .fold(0u8, |acc, d| acc * 10 + d);
+                    let (value, max) = self.focused_field();
+                    *value = input.min(max);
+                    self.remaining = self.duration();
                 }
+                false
             }
             TimerViewState::Running => {
-                if self.remaining.num_seconds() == 0 {
-                    self.state = TimerViewState::Finished;
+                let since_start = match self.timer.data.start_moments.last() {
+                    Some(start) => moment - *start,
+                    None => Duration::zero(),
+                };
+                self.remaining = self.timer.data.remaining - since_start;
+                if self.remaining <= Duration::zero() {
+                    self.timer.pause_at(moment);
+                    self.finish();
+                    return true;
                 }
+                false
             }
-            TimerViewState::Finished => {}
+            TimerViewState::Paused | TimerViewState::Finished => false,
         }
     }
 
     pub fn render(&self) -> String {
         match self.state {
             TimerViewState::Config => {
-                format!("{}:{}:{}", self.config.h, self.config.m, self.config.s)
-            }
-            TimerViewState::Running => {
-                format!("{}", self.remaining.hhmmssxxx())
-            }
-            TimerViewState::Finished => {
-                format!("Finished!")
+                format!("{:02}:{:02}:{:02}", self.config.h, self.config.m, self.config.s)
             }
+            TimerViewState::Running | TimerViewState::Paused => self.remaining.hhmmss(),
+            TimerViewState::Finished => "Finished!".to_string(),
         }
     }
 }
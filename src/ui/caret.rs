@@ -0,0 +1,85 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tui::text::Span;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How long the caret stays shown, then hidden, while blinking.
+pub const BLINK_PERIOD: Duration = Duration::from_millis(500);
+
+/// How the caret is drawn in the words being typed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CaretStyle {
+    /// The next letter is shown in reverse.
+    #[default]
+    Block,
+    /// A bar before the next letter, drawn with the terminal cursor.
+    Line,
+    /// The next letter is underlined.
+    Underline,
+    Off,
+}
+
+impl CaretStyle {
+    pub const ALL: [CaretStyle; 4] =
+        [CaretStyle::Block, CaretStyle::Line, CaretStyle::Underline, CaretStyle::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CaretStyle::Block => "block",
+            CaretStyle::Line => "line",
+            CaretStyle::Underline => "underline",
+            CaretStyle::Off => "off",
+        }
+    }
+}
+
+/// Whether a blinking caret is shown `idle` after it last moved. It stays shown while
+/// typing and only starts blinking once the keys stop.
+pub fn caret_shown(idle: Duration, blink: bool) -> bool {
+    !blink || (idle.as_millis() / BLINK_PERIOD.as_millis()).is_multiple_of(2)
+}
+
+/// Where a tape `width` columns wide starts with the caret `caret` columns into its line, as
+/// the columns of the line scrolled out of sight and the empty columns put before it.
+///
+/// The caret stays in the middle and the words move past it, so at the start of the line
+/// the tape is padded instead.
+pub fn tape_offset(caret: usize, width: usize) -> (usize, usize) {
+    let middle = width / 2;
+    (caret.saturating_sub(middle), middle.saturating_sub(caret))
+}
+
+/// The `width` columns of `spans` after the first `skip`, along with the index among them of
+/// the span that was `caret`. Wide graphemes cut in half by an edge are left as spaces.
+pub fn tape_window(
+    spans: Vec<Span<'static>>,
+    caret: Option<usize>,
+    skip: usize,
+    width: usize,
+) -> (Vec<Span<'static>>, Option<usize>) {
+    let mut shown = Vec::new();
+    let mut shown_caret = None;
+    let mut column = 0;
+    for (index, span) in spans.into_iter().enumerate() {
+        let mut content = String::new();
+        for g in span.content.graphemes(true) {
+            let end = column + g.width();
+            if column >= skip && end <= skip + width {
+                content.push_str(g);
+            } else if end > skip && column < skip + width {
+                content.push_str(&" ".repeat(end.min(skip + width) - column.max(skip)));
+            }
+            column = end;
+        }
+        if content.is_empty() {
+            continue;
+        }
+        if caret == Some(index) {
+            shown_caret = Some(shown.len());
+        }
+        shown.push(Span::styled(content, span.style));
+    }
+    (shown, shown_caret)
+}
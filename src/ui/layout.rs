@@ -0,0 +1,59 @@
+use tui::layout::Rect;
+
+/// The smallest terminal the app can be used in.
+pub const MIN_WIDTH: u16 = 30;
+pub const MIN_HEIGHT: u16 = 10;
+/// Terminals narrower or shorter than this get the compact layout.
+pub const COMPACT_WIDTH: u16 = 80;
+pub const COMPACT_HEIGHT: u16 = 24;
+/// The row of the word stream the line being typed is kept on, with room for the line
+/// typed before it above.
+const CARET_ROW: usize = 1;
+
+/// How much room the terminal leaves for the screens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeClass {
+    /// Nothing but a notice asking for a bigger terminal fits.
+    TooSmall,
+    /// Margins and side panels are dropped to leave room for what matters.
+    Compact,
+    Regular,
+}
+
+impl SizeClass {
+    pub fn of(area: Rect) -> Self {
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            SizeClass::TooSmall
+        } else if area.width < COMPACT_WIDTH || area.height < COMPACT_HEIGHT {
+            SizeClass::Compact
+        } else {
+            SizeClass::Regular
+        }
+    }
+
+    /// The margin around a screen, `regular` when there is room for it.
+    pub fn margin(&self, regular: u16) -> u16 {
+        match self {
+            SizeClass::Regular => regular,
+            _ => 0,
+        }
+    }
+
+    pub fn is_compact(&self) -> bool {
+        *self != SizeClass::Regular
+    }
+}
+
+/// Which lines of words to show with `height` rows of room while typing on `current_line`,
+/// as the first line to show and the number of empty rows to put above it.
+///
+/// The line being typed stays on the same row, so the eyes do not have to follow it and a
+/// resize that reflows the words keeps it in place. With `reduce_motion` the words turn a
+/// whole page at a time instead.
+pub fn scroll(current_line: usize, height: usize, reduce_motion: bool) -> (usize, usize) {
+    if reduce_motion {
+        return (current_line - current_line % height.max(1), 0);
+    }
+    let row = if height > CARET_ROW + 1 { CARET_ROW } else { 0 };
+    (current_line.saturating_sub(row), row.saturating_sub(current_line))
+}
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
+
+/// Words shown at a time in memory mode.
+pub const MEMORY_WORDS: usize = 5;
+/// How long they stay shown before they have to be typed from memory.
+pub const MEMORY_TIME: Duration = Duration::from_secs(3);
+
+/// Which words memory mode shows. A few words at a time are shown briefly then hidden,
+/// and the next few are shown once the typist reaches them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Memory {
+    /// The first of the words last shown.
+    pub first: usize,
+    pub shown_at: Instant,
+}
+
+impl Memory {
+    pub fn new(now: Instant) -> Self {
+        Memory { first: 0, shown_at: now }
+    }
+
+    /// Shows the next words once `current`, the word being typed, has gone past those shown.
+    pub fn update(&mut self, current: usize, now: Instant) {
+        if current >= self.first + MEMORY_WORDS {
+            self.first = current;
+            self.shown_at = now;
+        }
+    }
+
+    /// Whether the letters of `word` still to be typed are hidden at `now`.
+    pub fn is_hidden(&self, word: usize, now: Instant) -> bool {
+        let shown = word < self.first + MEMORY_WORDS
+            && now.saturating_duration_since(self.shown_at) < MEMORY_TIME;
+        word >= self.first && !shown
+    }
+}
+
+/// `text` with every column covered, so hidden words keep their place in the line.
+pub fn masked(text: &str) -> String {
+    "_".repeat(text.width())
+}
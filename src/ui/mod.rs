@@ -1 +1,11 @@
+pub mod caret;
+pub mod layout;
+pub mod memory;
+pub mod mouse;
+pub mod plain;
+pub mod stats_bar;
+pub mod terminal;
+#[allow(clippy::module_inception)]
 pub mod ui;
+#[allow(clippy::module_inception)]
+mod tests;
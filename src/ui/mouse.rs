@@ -0,0 +1,222 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::TableState;
+
+use crate::app::{App, State};
+use crate::keymap::keymap::Action;
+
+/// A table as it was last drawn, to tell which row a click landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TableArea {
+    /// Where the rows are drawn, below the header.
+    pub rows: Rect,
+    /// Rows scrolled past to keep the selected one in view.
+    pub offset: usize,
+    pub len: usize,
+    /// Lines each row takes, including its bottom margin.
+    pub row_height: u16,
+    /// The columns of a value cycled by clicking it, backwards left of its middle.
+    pub value: Option<(u16, u16)>,
+}
+
+impl TableArea {
+    /// A table with `len` rows of one line plus `row_height - 1` lines of margin drawn in
+    /// `area` from its first row, see `from_top`, under a header taking `header` lines.
+    pub fn new(
+        area: Rect,
+        header: u16,
+        len: usize,
+        row_height: u16,
+        selected: Option<usize>,
+    ) -> TableArea {
+        let rows = Rect::new(
+            area.x,
+            area.y + header.min(area.height),
+            area.width,
+            area.height.saturating_sub(header),
+        );
+        TableArea {
+            rows,
+            offset: scroll_offset(selected, len, row_height, rows.height),
+            len,
+            row_height: row_height.max(1),
+            value: None,
+        }
+    }
+
+    /// Records where the last of `widths` is drawn, given the ">> " highlight symbol shown
+    /// while a row is selected. The column is taken to run to the edge of the table.
+    pub fn with_value(mut self, widths: &[Constraint], selected: bool) -> TableArea {
+        let mut constraints = Vec::new();
+        if selected {
+            constraints.push(Constraint::Length(3));
+        }
+        for width in widths {
+            constraints.push(*width);
+            constraints.push(Constraint::Length(1));
+        }
+        constraints.pop();
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(Rect::new(self.rows.x, self.rows.y, self.rows.width, 1));
+        self.value = columns.last().map(|c| (c.x, c.width));
+        self
+    }
+
+    /// The index of the row at a position, if there is one.
+    pub fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !contains(self.rows, column, row) {
+            return None;
+        }
+        let index = self.offset + ((row - self.rows.y) / self.row_height) as usize;
+        (index < self.len).then_some(index)
+    }
+}
+
+/// Makes a tui table forget how far it was scrolled, so it is drawn from its first row and
+/// scrolled just far enough to show the selected row, the way `scroll_offset` works it out.
+pub fn from_top(state: &mut TableState) {
+    let selected = state.selected();
+    state.select(None);
+    state.select(selected);
+}
+
+/// The first row a tui table drawn from its first row shows, following its `get_row_bounds`.
+pub fn scroll_offset(
+    selected: Option<usize>,
+    len: usize,
+    row_height: u16,
+    max_height: u16,
+) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let row_height = row_height.max(1);
+    let (mut start, mut end) = (0, 0);
+    let mut height: u16 = 0;
+    while end < len && height < max_height {
+        height += row_height;
+        end += 1;
+    }
+    let selected = selected.unwrap_or(0).min(len - 1);
+    while selected >= end {
+        height = height.saturating_add(row_height);
+        end += 1;
+        while height > max_height {
+            height = height.saturating_sub(row_height);
+            start += 1;
+        }
+    }
+    start
+}
+
+/// The bars of a bar chart as last drawn, to tell which result the mouse is over.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BarsArea {
+    /// Inside the chart border.
+    pub area: Rect,
+    pub bar_width: u16,
+    pub bar_gap: u16,
+    /// Index of the result drawn as the first bar.
+    pub first: usize,
+    /// Bars drawn.
+    pub len: usize,
+}
+
+impl BarsArea {
+    /// The index of the result whose bar is at a position, gaps included.
+    pub fn bar_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !contains(self.area, column, row) {
+            return None;
+        }
+        let bar = ((column - self.area.x) / (self.bar_width + self.bar_gap)) as usize;
+        (bar < self.len).then_some(self.first + bar)
+    }
+}
+
+/// Where the clickable parts of the current screen were drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MouseAreas {
+    pub table: Option<TableArea>,
+    pub bars: Option<BarsArea>,
+    /// The error toast, which a click dismisses.
+    pub toast: Option<Rect>,
+    /// The last place the mouse was seen, for tooltips.
+    pub hover: Option<(u16, u16)>,
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+}
+
+/// Handles what a mouse event does by itself, and returns the action it stands for when it
+/// does what a key would: clicking a menu row selects it, the wheel moves through lists,
+/// clicking a setting's value cycles it and clicking the selected result opens it.
+pub fn mouse_action(app: &mut App, event: MouseEvent) -> Option<Action> {
+    let (column, row) = (event.column, event.row);
+    let clicked = event.kind == MouseEventKind::Down(MouseButton::Left);
+
+    // A click on the error toast dismisses it, other clicks go to the screen under it
+    if let Some(toast) = app.mouse_areas.toast {
+        if clicked && contains(toast, column, row) {
+            app.notice = None;
+            app.dismiss_error();
+            return None;
+        }
+    }
+    if app.show_help {
+        if clicked {
+            app.notice = None;
+            app.show_help = false;
+        }
+        return None;
+    }
+    if app.profile_edit.is_some() || app.history_edit.is_some() {
+        return None;
+    }
+
+    let areas = app.mouse_areas;
+    match event.kind {
+        MouseEventKind::Moved | MouseEventKind::Drag(_) => {
+            app.mouse_areas.hover = Some((column, row));
+            None
+        }
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let up = event.kind == MouseEventKind::ScrollUp;
+            match app.state {
+                State::Chart => {
+                    app.scroll_chart(if up { -1 } else { 1 });
+                    None
+                }
+                State::MainMenu | State::Settings | State::Profiles => {
+                    Some(if up { Action::Up } else { Action::Down })
+                }
+                State::History if !app.history_detail => {
+                    Some(if up { Action::Up } else { Action::Down })
+                }
+                _ => None,
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            let table = areas.table?;
+            let index = table.row_at(column, row)?;
+            app.notice = None;
+            let was_selected = app.selected_row() == Some(index);
+            app.select_row(index);
+            match app.state {
+                State::MainMenu | State::Profiles => Some(Action::Select),
+                State::History if was_selected => Some(Action::Select),
+                State::Settings => match table.value {
+                    Some((x, width)) if column >= x && column < x + width => {
+                        Some(if column < x + width / 2 { Action::Left } else { Action::Right })
+                    }
+                    _ if was_selected => Some(Action::Right),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
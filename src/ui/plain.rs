@@ -0,0 +1,270 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyModifiers,
+};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::{App, InputMode};
+use crate::engine::engine::{TestMode, TypingEngine, TypingTestState};
+use crate::error::ErrorKind;
+use crate::serializers::wpm_results::WpmResult;
+use crate::ui::terminal::Signals;
+
+/// How long to wait for a key before checking the clock again.
+const TICK_RATE: Duration = Duration::from_millis(100);
+/// Time left in a time test when it is announced.
+const TIME_WARNING: Duration = Duration::from_secs(10);
+
+/// Raw mode without the alternate screen, so every line stays in the scrollback where a
+/// screen reader can read it. Pastes come in whole so they can be refused rather than typed.
+/// Restores the terminal when dropped.
+struct RawGuard;
+
+impl RawGuard {
+    fn new() -> io::Result<Self> {
+        enter()?;
+        Ok(RawGuard)
+    }
+
+    /// Hands the terminal back to the shell and stops the process like ctrl-z normally
+    /// would, then sets the terminal up again once the job is continued.
+    fn suspend(&self) -> io::Result<()> {
+        restore()?;
+        #[cfg(unix)]
+        signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+        enter()
+    }
+}
+
+impl Drop for RawGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+fn enter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnableBracketedPaste)
+}
+
+fn restore() -> io::Result<()> {
+    let _ = execute!(io::stdout(), DisableBracketedPaste);
+    disable_raw_mode()
+}
+
+/// Writes `text` as a line of its own. Raw mode needs the carriage return.
+fn say(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "{}\r\n", text)?;
+    out.flush()
+}
+
+/// Stops the process until the shell continues it, pausing a running test meanwhile, then
+/// picks the test up again where it was.
+fn suspend(out: &mut impl Write, app: &mut App, guard: &RawGuard) -> io::Result<()> {
+    let typing = app.input_mode == InputMode::Typing;
+    if typing {
+        app.pause_test();
+    }
+    say(out, "")?;
+    guard.suspend()?;
+    say(out, "Resumed.")?;
+    if typing {
+        app.start_typing();
+        if let Some(prompt) = word_prompt(&app.engine) {
+            say(out, &prompt)?;
+        }
+        write!(out, "{}", app.engine.input)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads out the errors waiting to be shown, each on a line of its own.
+fn say_errors(out: &mut impl Write, app: &mut App) -> io::Result<()> {
+    while let Some(e) = app.errors.pop_front() {
+        say(out, "")?;
+        say(out, &format!("Warning: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Announces the word to type next, `None` once the test is over.
+pub fn word_prompt(engine: &TypingEngine) -> Option<String> {
+    if !matches!(engine.state, TypingTestState::NotStarted | TypingTestState::Running) {
+        return None;
+    }
+    let word = engine.current_word()?;
+    let number = engine.typed.len() + 1;
+    Some(match engine.mode {
+        TestMode::Words(count) => format!("Word {} of {}: {}", number, count, word),
+        TestMode::Time(_) => format!("Word {}: {}", number, word),
+    })
+}
+
+/// What to say about the word just submitted, nothing when it was right.
+pub fn word_feedback(expected: &str, typed: &str) -> Option<String> {
+    match expected == typed {
+        true => None,
+        false => Some(format!("Wrong, typed {} for {}", typed, expected)),
+    }
+}
+
+/// The result of a finished test as sentences.
+pub fn result_lines(result: &WpmResult, engine: &TypingEngine) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "Test over. {:.0} WPM, {:.0} adjusted, {:.1}% accuracy in {:.0} seconds.",
+            result.wpm,
+            result.awpm,
+            result.accuracy * 100.0,
+            result.duration * 60.0
+        ),
+        format!(
+            "{} words correct, {} incorrect.",
+            engine.correct_words(),
+            engine.incorrect_words()
+        ),
+    ];
+    if !result.is_valid() {
+        let reasons = result.flags.iter().map(|f| f.description()).collect::<Vec<_>>();
+        lines.push(format!("Flagged, not counted as a best: {}.", reasons.join(", ")));
+    }
+    lines
+}
+
+fn start_test(out: &mut impl Write, app: &mut App) -> io::Result<()> {
+    app.start_typing();
+    say(out, &format!("New test, {}. Type each word followed by space.", app.engine.mode))?;
+    match word_prompt(&app.engine) {
+        Some(prompt) => say(out, &prompt),
+        None => Ok(()),
+    }
+}
+
+/// Runs typing tests as plain lines of text instead of the full screen interface: each word
+/// is announced on its own line, typed letters are echoed, and results are read out as
+/// sentences. Escape or ctrl-c quits.
+pub fn run_plain(app: &mut App, signals: &Signals) -> Result<(), ErrorKind> {
+    let guard = RawGuard::new()?;
+    let mut out = io::stdout();
+
+    say_errors(&mut out, app)?;
+    say(&mut out, "Bunbuntype plain mode. Press escape to quit.")?;
+    start_test(&mut out, app)?;
+
+    let mut announced = false;
+    let mut warned = false;
+    loop {
+        if signals.take_quit() {
+            break;
+        }
+        if signals.take_suspend() {
+            suspend(&mut out, app, &guard)?;
+        }
+
+        app.on_tick();
+        // Saving a result or the config can fail at any point of the session
+        say_errors(&mut out, app)?;
+        if app.engine.state == TypingTestState::Failed && !announced {
+            announced = true;
+            say(&mut out, "")?;
+            let reason = app.engine.difficulty.level.failure();
+            say(&mut out, &format!("Test failed, {}. Nothing was recorded.", reason))?;
+            say(&mut out, "Press enter for another test or escape to quit.")?;
+        }
+        if app.engine.state == TypingTestState::End && !announced {
+            announced = true;
+            say(&mut out, "")?;
+            let result = app.last_result.clone().or_else(|| app.engine.result());
+            if let Some(result) = result {
+                for line in result_lines(&result, &app.engine) {
+                    say(&mut out, &line)?;
+                }
+            }
+            if let Some(notice) = app.notice.take() {
+                say(&mut out, &notice)?;
+            }
+            say(&mut out, "Press enter for another test or escape to quit.")?;
+        }
+        if let (Some(remaining), TestMode::Time(secs)) =
+            (app.engine.remaining_at(Instant::now()), app.engine.mode)
+        {
+            let warn = app.engine.state == TypingTestState::Running
+                && remaining <= TIME_WARNING
+                && Duration::from_secs(secs) > TIME_WARNING;
+            if warn && !warned {
+                warned = true;
+                say(&mut out, "")?;
+                say(&mut out, &format!("{} seconds left", TIME_WARNING.as_secs()))?;
+            }
+        }
+
+        if !event::poll(TICK_RATE)? {
+            continue;
+        }
+
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            Event::Paste(_) if app.input_mode == InputMode::Typing => {
+                say(&mut out, "")?;
+                say(&mut out, "Pasting is disabled during tests")?;
+                continue;
+            }
+            _ => continue,
+        };
+
+        match key.code {
+            KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                suspend(&mut out, app, &guard)?
+            }
+            KeyCode::Enter if announced => {
+                announced = false;
+                warned = false;
+                start_test(&mut out, app)?;
+            }
+            KeyCode::Char(c) if app.input_mode == InputMode::Typing => {
+                let typed = app.engine.typed.len();
+                let keystrokes = app.engine.keystrokes.len();
+                app.type_char(c);
+                // Keys held back by stop on error go unechoed, like ignored ones
+                let typed_in = app.engine.keystrokes.get(keystrokes).is_some_and(|k| !k.blocked);
+                if !typed_in {
+                    continue;
+                }
+                if !c.is_whitespace() {
+                    write!(out, "{}", c)?;
+                    out.flush()?;
+                }
+                if app.engine.typed.len() > typed {
+                    say(&mut out, "")?;
+                    let engine = &app.engine;
+                    let feedback = word_feedback(&engine.words[typed], &engine.typed[typed]);
+                    // Blind mode keeps mistakes to the results
+                    if let Some(feedback) = feedback.filter(|_| !app.config.blind_mode) {
+                        say(&mut out, &feedback)?;
+                    }
+                    if let Some(prompt) = word_prompt(engine) {
+                        say(&mut out, &prompt)?;
+                    }
+                }
+            }
+            KeyCode::Backspace if app.input_mode == InputMode::Typing => {
+                let before = app.engine.input.width();
+                app.backspace();
+                let erased = before - app.engine.input.width();
+                write!(out, "{}", "\x08 \x08".repeat(erased))?;
+                out.flush()?;
+            }
+            _ => {}
+        }
+    }
+
+    say(&mut out, "")?;
+    Ok(())
+}
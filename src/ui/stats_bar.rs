@@ -0,0 +1,79 @@
+use hhmmss::Hhmmss;
+use serde::{Deserialize, Serialize};
+use crate::engine::engine::LiveStats;
+
+/// A figure the live stats bar can show while typing.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatField {
+    /// Time left in time mode, words left in words mode.
+    Left,
+    Wpm,
+    Raw,
+    Accuracy,
+    Errors,
+}
+
+impl StatField {
+    /// In the order the bar shows them.
+    pub const ALL: [StatField; 5] = [
+        StatField::Left,
+        StatField::Wpm,
+        StatField::Raw,
+        StatField::Accuracy,
+        StatField::Errors,
+    ];
+
+    /// The fields shown until others are picked.
+    pub const DEFAULT: [StatField; 3] = [StatField::Left, StatField::Wpm, StatField::Accuracy];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatField::Left => "Live time left",
+            StatField::Wpm => "Live WPM",
+            StatField::Raw => "Live raw WPM",
+            StatField::Accuracy => "Live accuracy",
+            StatField::Errors => "Live errors",
+        }
+    }
+
+    /// Whether the field gives away mistakes, which blind mode keeps to the results.
+    pub fn reveals_mistakes(&self) -> bool {
+        matches!(self, StatField::Wpm | StatField::Accuracy | StatField::Errors)
+    }
+
+    pub fn text(&self, stats: &LiveStats) -> String {
+        match self {
+            StatField::Left => match stats.time_left {
+                Some(left) => left.hhmmss(),
+                None => format!("{} left", stats.words_left),
+            },
+            StatField::Wpm => format!("{:.0} wpm", stats.wpm),
+            StatField::Raw => format!("{:.0} raw", stats.raw),
+            StatField::Accuracy => format!("{:.1}%", stats.accuracy * 100.0),
+            StatField::Errors => match stats.errors {
+                1 => "1 error".to_string(),
+                n => format!("{} errors", n),
+            },
+        }
+    }
+}
+
+/// The text of each of `fields` for `stats`, in the order of `StatField::ALL`.
+pub fn stats_bar(fields: &[StatField], stats: &LiveStats) -> Vec<String> {
+    StatField::ALL
+        .iter()
+        .filter(|field| fields.contains(field))
+        .map(|field| field.text(stats))
+        .collect()
+}
+
+/// Shows `field` on the bar if it is hidden, hides it otherwise.
+pub fn toggle_field(fields: &mut Vec<StatField>, field: StatField) {
+    match fields.iter().position(|f| *f == field) {
+        Some(index) => {
+            fields.remove(index);
+        }
+        None => fields.push(field),
+    }
+}
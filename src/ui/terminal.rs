@@ -0,0 +1,154 @@
+use std::io::{self, Write};
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use chrono::Local;
+use crossterm::{
+    cursor::{CursorShape, SetCursorShape, Show},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// Whether the terminal is currently in raw mode on the alternate screen, so the guard,
+/// the panic hook and suspending never restore it twice.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Puts the cursor back to the shape the user set up for their terminal.
+const DEFAULT_CURSOR_SHAPE: &str = "\x1b[0 q";
+
+/// Switches to raw mode and the alternate screen. Pastes arrive as one event instead of
+/// a burst of key presses.
+pub fn enter() -> io::Result<()> {
+    if ACTIVE.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)
+}
+
+/// Puts the terminal back the way the shell expects it. Safe to call more than once.
+pub fn restore() -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let raw = disable_raw_mode();
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        Show
+    )?;
+    write!(io::stdout(), "{}", DEFAULT_CURSOR_SHAPE)?;
+    io::stdout().flush()?;
+    raw
+}
+
+/// Draws the cursor as a bar, or as the terminal draws it by default.
+pub fn bar_cursor(bar: bool) -> io::Result<()> {
+    match bar {
+        true => execute!(io::stdout(), SetCursorShape(CursorShape::Line)),
+        false => {
+            write!(io::stdout(), "{}", DEFAULT_CURSOR_SHAPE)?;
+            io::stdout().flush()
+        }
+    }
+}
+
+/// Keeps the terminal set up for the app while alive and restores it when dropped,
+/// including when unwinding from a panic or returning early with an error.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        enter()?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+/// Restores the terminal before the panic message is printed, so it does not end up on
+/// the alternate screen or mangled by raw mode, then points at a crash log with the details.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        default_hook(info);
+        match write_crash_log(info) {
+            Ok(path) => eprintln!("Crash log written to {}", path.display()),
+            Err(e) => eprintln!("Could not write crash log: {}", e),
+        }
+    }));
+}
+
+fn write_crash_log(info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let now = Local::now();
+    let path = std::env::temp_dir().join(format!(
+        "{}-crash-{}.log",
+        env!("CARGO_PKG_NAME"),
+        now.format("%Y%m%d-%H%M%S")
+    ));
+    let mut file = std::fs::File::create(&path)?;
+    writeln!(
+        file,
+        "{} {} crashed at {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339()
+    )?;
+    writeln!(file, "{}", info)?;
+    writeln!(file)?;
+    writeln!(file, "{}", std::backtrace::Backtrace::force_capture())?;
+    Ok(path)
+}
+
+/// Flags raised by process signals, checked by the event loop on every tick.
+pub struct Signals {
+    quit: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+}
+
+impl Signals {
+    /// Catches SIGINT and SIGTERM to quit cleanly, and SIGTSTP to suspend. In raw mode
+    /// ctrl-c and ctrl-z arrive as key presses instead, these cover signals sent by `kill`.
+    pub fn register() -> io::Result<Self> {
+        let signals = Signals {
+            quit: Arc::new(AtomicBool::new(false)),
+            suspend: Arc::new(AtomicBool::new(false)),
+        };
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::{SIGINT, SIGTERM, SIGTSTP};
+            use signal_hook::flag::register;
+            register(SIGINT, Arc::clone(&signals.quit))?;
+            register(SIGTERM, Arc::clone(&signals.quit))?;
+            register(SIGTSTP, Arc::clone(&signals.suspend))?;
+        }
+        Ok(signals)
+    }
+
+    pub fn take_quit(&self) -> bool {
+        self.quit.swap(false, Ordering::SeqCst)
+    }
+
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Hands the terminal back to the shell and stops the process like ctrl-z normally would.
+/// Returns once the job is continued, with the terminal set up again; the caller has to
+/// clear it so the next draw repaints everything.
+pub fn suspend() -> io::Result<()> {
+    restore()?;
+    #[cfg(unix)]
+    signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+    enter()
+}
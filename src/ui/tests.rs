@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::engine::engine::{LiveStats, TestMode, TypingEngine};
+    use crate::ui::plain::{result_lines, word_feedback, word_prompt};
+    use crate::ui::stats_bar::{stats_bar, toggle_field, StatField};
+    use crate::ui::caret::{caret_shown, tape_offset, tape_window};
+    use crate::ui::layout::{scroll, SizeClass};
+    use crate::ui::memory::{masked, Memory, MEMORY_TIME, MEMORY_WORDS};
+    use crate::ui::mouse::{scroll_offset, BarsArea, TableArea};
+    use crate::ui::ui::{line_starts, word_width};
+    use tui::layout::{Constraint, Rect};
+    use tui::style::{Color, Style};
+    use tui::text::Span;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_line_starts() {
+        assert_eq!(line_starts(&words(&["ab", "cd", "ef"]), 6, false), vec![0, 2]);
+        assert_eq!(line_starts(&words(&["abcdefgh", "ij"]), 4, false), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_line_starts_wide_and_accented() {
+        // Accents combine into the letter before them and take no column of their own
+        assert_eq!(line_starts(&words(&["cafe\u{301}", "née"]), 9, false), vec![0]);
+
+        // CJK characters take two columns each
+        assert_eq!(line_starts(&words(&["日本", "語"]), 8, false), vec![0]);
+        assert_eq!(line_starts(&words(&["日本語", "日本語"]), 12, false), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_large_text() {
+        assert_eq!(word_width("cat", true), 5);
+        assert_eq!(word_width("日本", true), 5);
+        assert_eq!(word_width("e\u{301}", true), 1);
+
+        // Words take their spaced width plus three columns between them
+        assert_eq!(line_starts(&words(&["ab", "cd"]), 12, true), vec![0]);
+        assert_eq!(line_starts(&words(&["ab", "cd"]), 11, true), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_scroll() {
+        // The line being typed stays on the second row, with an empty one above the first
+        assert_eq!(scroll(0, 4, false), (0, 1));
+        assert_eq!(scroll(1, 4, false), (0, 0));
+        assert_eq!(scroll(5, 4, false), (4, 0));
+        assert_eq!(scroll(5, 2, false), (5, 0));
+
+        // Pages turn only once the current line moves past the page shown
+        assert_eq!(scroll(3, 4, true), (0, 0));
+        assert_eq!(scroll(4, 4, true), (4, 0));
+        assert_eq!(scroll(2, 0, true), (2, 0));
+    }
+
+    #[test]
+    fn test_size_class() {
+        assert_eq!(SizeClass::of(Rect::new(0, 0, 120, 40)), SizeClass::Regular);
+        assert_eq!(SizeClass::of(Rect::new(0, 0, 60, 40)), SizeClass::Compact);
+        assert_eq!(SizeClass::of(Rect::new(0, 0, 120, 20)), SizeClass::Compact);
+        assert_eq!(SizeClass::of(Rect::new(0, 0, 29, 40)), SizeClass::TooSmall);
+        assert_eq!(SizeClass::Compact.margin(3), 0);
+        assert_eq!(SizeClass::Regular.margin(3), 3);
+    }
+
+    #[test]
+    fn test_plain_announcements() {
+        let start = Instant::now();
+        let mut engine = TypingEngine::new(TestMode::Words(2), words(&["cat", "sat"]));
+        assert_eq!(word_prompt(&engine).as_deref(), Some("Word 1 of 2: cat"));
+
+        for c in "cta ".chars() {
+            engine.type_char_at(c, start);
+        }
+        assert_eq!(word_prompt(&engine).as_deref(), Some("Word 2 of 2: sat"));
+        assert_eq!(word_feedback("cat", "cta").as_deref(), Some("Wrong, typed cta for cat"));
+        assert_eq!(word_feedback("cat", "cat"), None);
+
+        for c in "sat".chars() {
+            engine.type_char_at(c, start + std::time::Duration::from_secs(6));
+        }
+        assert_eq!(word_prompt(&engine), None);
+        let lines = result_lines(&engine.result().unwrap(), &engine);
+        assert_eq!(lines[1], "1 words correct, 1 incorrect.");
+
+        let engine = TypingEngine::new(TestMode::Time(30), words(&["cat"]));
+        assert_eq!(word_prompt(&engine).as_deref(), Some("Word 1: cat"));
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        // Five rows of two lines fit in ten
+        assert_eq!(scroll_offset(Some(4), 8, 2, 10), 0);
+        assert_eq!(scroll_offset(Some(5), 8, 2, 10), 1);
+        assert_eq!(scroll_offset(Some(7), 8, 2, 10), 3);
+        assert_eq!(scroll_offset(None, 8, 2, 10), 0);
+        assert_eq!(scroll_offset(Some(3), 0, 2, 10), 0);
+    }
+
+    #[test]
+    fn test_row_at() {
+        let table = TableArea::new(Rect::new(1, 1, 20, 8), 2, 8, 2, Some(5));
+        assert_eq!(table.offset, 3);
+
+        // The header and the margin below it are not rows
+        assert_eq!(table.row_at(5, 2), None);
+        assert_eq!(table.row_at(5, 3), Some(3));
+        assert_eq!(table.row_at(5, 4), Some(3));
+        assert_eq!(table.row_at(5, 8), Some(5));
+        assert_eq!(table.row_at(21, 8), None);
+
+        let table = TableArea::new(Rect::new(0, 0, 20, 10), 0, 2, 2, None);
+        assert_eq!(table.row_at(0, 3), Some(1));
+        assert_eq!(table.row_at(0, 4), None);
+
+        // The value column starts after the other column and the highlight symbol
+        let widths = [Constraint::Percentage(40), Constraint::Percentage(60)];
+        let table = TableArea::new(Rect::new(0, 0, 40, 10), 2, 8, 2, Some(0));
+        assert_eq!(table.with_value(&widths, false).value.map(|(x, _)| x), Some(16 + 1));
+        assert_eq!(table.with_value(&widths, true).value.map(|(x, _)| x), Some(3 + 16 + 1));
+    }
+
+    #[test]
+    fn test_bar_at() {
+        let area = Rect::new(1, 1, 35, 10);
+        let bars = BarsArea { area, bar_width: 9, bar_gap: 1, first: 4, len: 3 };
+        assert_eq!(bars.bar_at(1, 5), Some(4));
+        assert_eq!(bars.bar_at(10, 5), Some(4));
+        assert_eq!(bars.bar_at(11, 5), Some(5));
+        assert_eq!(bars.bar_at(31, 5), None);
+        assert_eq!(bars.bar_at(5, 11), None);
+    }
+
+    #[test]
+    fn test_caret_blink() {
+        assert!(caret_shown(Duration::from_millis(1700), false));
+        assert!(caret_shown(Duration::from_millis(200), true));
+        assert!(!caret_shown(Duration::from_millis(700), true));
+        assert!(caret_shown(Duration::from_millis(1000), true));
+    }
+
+    #[test]
+    fn test_tape() {
+        // The start of the line is padded until the caret reaches the middle
+        assert_eq!(tape_offset(0, 20), (0, 10));
+        assert_eq!(tape_offset(4, 20), (0, 6));
+        assert_eq!(tape_offset(25, 20), (15, 0));
+
+        let red = Style::default().fg(Color::Red);
+        let spans = vec![Span::raw("ab "), Span::styled("c", red), Span::raw("日本 ")];
+        let (shown, caret) = tape_window(spans.clone(), Some(1), 2, 5);
+        assert_eq!(shown, vec![Span::raw(" "), Span::styled("c", red), Span::raw("日 ")]);
+        assert_eq!(caret, Some(1));
+
+        // Wide graphemes cut by an edge become spaces, the caret can scroll out of sight
+        let (shown, caret) = tape_window(spans, Some(1), 5, 3);
+        assert_eq!(shown, vec![Span::raw(" 本")]);
+        assert_eq!(caret, None);
+    }
+
+    #[test]
+    fn test_memory() {
+        let start = Instant::now();
+        let mut memory = Memory::new(start);
+        assert!(!memory.is_hidden(0, start));
+        assert!(!memory.is_hidden(MEMORY_WORDS - 1, start));
+        assert!(memory.is_hidden(MEMORY_WORDS, start));
+
+        // Shown words are hidden once their time is up
+        let later = start + MEMORY_TIME;
+        assert!(memory.is_hidden(0, later));
+
+        // The next words are shown once the typist gets past the ones shown
+        memory.update(MEMORY_WORDS - 1, later);
+        assert!(memory.is_hidden(MEMORY_WORDS - 1, later));
+        memory.update(MEMORY_WORDS, later);
+        assert!(!memory.is_hidden(MEMORY_WORDS, later));
+        assert!(!memory.is_hidden(0, later));
+        assert!(memory.is_hidden(MEMORY_WORDS * 2, later));
+
+        assert_eq!(masked("abc"), "___");
+        assert_eq!(masked("日本"), "____");
+    }
+
+    #[test]
+    fn test_stats_bar() {
+        let stats = LiveStats {
+            time_left: Some(Duration::from_secs(75)),
+            words_left: 0,
+            wpm: 61.6,
+            raw: 70.2,
+            accuracy: 0.9641,
+            errors: 1,
+        };
+        let mut fields = vec![StatField::Errors, StatField::Left];
+        assert_eq!(stats_bar(&fields, &stats), vec!["00:01:15", "1 error"]);
+
+        // Fields show in the same order whichever was turned on first
+        toggle_field(&mut fields, StatField::Left);
+        toggle_field(&mut fields, StatField::Raw);
+        toggle_field(&mut fields, StatField::Accuracy);
+        toggle_field(&mut fields, StatField::Wpm);
+        assert_eq!(stats_bar(&fields, &stats), vec!["62 wpm", "70 raw", "96.4%", "1 error"]);
+
+        let words = LiveStats { time_left: None, words_left: 7, ..stats };
+        assert_eq!(stats_bar(&[StatField::Left], &words), vec!["7 left"]);
+        assert!(stats_bar(&[], &stats).is_empty());
+
+        let shown = StatField::ALL.iter().filter(|f| !f.reveals_mistakes()).collect::<Vec<_>>();
+        assert_eq!(shown, vec![&StatField::Left, &StatField::Raw]);
+    }
+}
@@ -1,98 +1,297 @@
-use rand::seq::SliceRandom;
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::Color::Rgb;
-use tui::style::{Color, Modifier, Style};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{BarChart, Block, Borders, Cell, Clear, Paragraph, Row, Table};
+use tui::symbols::Marker;
+use tui::widgets::{
+    Axis, BarChart, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, Paragraph, Row,
+    Sparkline, Table, Wrap,
+};
 use tui::{Frame, Terminal};
 
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode};
 
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use crate::app::{App, InputMode, State, TypingTestState};
+use crate::app::{App, HistoryEdit, InputMode, ProfileEdit, Setting, State};
+use crate::engine::engine::{grapheme_matches, Sample, TestMode, TypingTestState};
 use crate::error::ErrorKind;
+use crate::goals::goals::DailyGoal;
+use crate::keymap::keymap::{Action, Screen};
+use crate::history::browser::Column;
+use crate::language::language::Direction as LanguageDirection;
+use crate::serializers::wpm_results::WpmResult;
+use crate::timer::timer::{TimerView, TimerViewState};
+use crate::ui::caret::{caret_shown, tape_offset, tape_window, CaretStyle};
+use crate::ui::layout::{scroll, SizeClass, MIN_HEIGHT, MIN_WIDTH};
+use crate::ui::memory::masked;
+use crate::ui::mouse::{from_top, mouse_action, BarsArea, TableArea};
+use crate::ui::stats_bar::{stats_bar, StatField};
+use crate::ui::terminal::{self, Signals};
 
-pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), ErrorKind> {
+/// How often the screen is redrawn while no input arrives, so running clocks stay current.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+pub fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    signals: &Signals,
+) -> Result<(), ErrorKind> {
+    let mut bar = false;
     loop {
+        if signals.take_quit() {
+            break;
+        }
+        if signals.take_suspend() {
+            suspend(terminal, app)?;
+            bar = false;
+        }
+
+        // A line caret needs the terminal cursor drawn as a bar
+        if bar != (app.config.caret == CaretStyle::Line) {
+            bar = !bar;
+            terminal::bar_cursor(bar)?;
+        }
+
+        app.on_tick();
         terminal.draw(|f| ui(f, app))?;
 
-        if let TypingTestState::Running = app.typing_test_state {
-            if app.current_time == 0 {
-                app.typing_test_state = TypingTestState::End;
+        if app.bell {
+            app.bell = false;
+            io::stdout().write_all(b"\x07")?;
+            io::stdout().flush()?;
+        }
+
+        if !event::poll(TICK_RATE)? {
+            continue;
+        }
+
+        let event = event::read()?;
+        // The next draw lays everything out for the new size, with the words reflowed
+        // around the one being typed
+        if let Event::Resize(..) = event {
+            terminal.autoresize()?;
+            continue;
+        }
+        if let Event::Paste(text) = &event {
+            app.paste(text);
+            continue;
+        }
+        if let Event::Mouse(mouse) = event {
+            if let Some(action) = mouse_action(app, mouse) {
+                if perform(terminal, app, action)? {
+                    break;
+                }
             }
+            continue;
         }
 
-        if let Event::Key(key) = event::read()? {
-            match app.input_mode {
-                InputMode::Normal => match key.code {
-                    KeyCode::Char('q') => {
-                        break;
-                    }
-                    KeyCode::Up => {
-                        app.up();
-                    }
-                    KeyCode::Down => {
-                        app.down();
-                    }
-                    KeyCode::Enter => {
-                        if app.state == State::MainMenu {
-                            match app.table_state.selected() {
-                                Some(0) => {
-                                    app.state = State::TypingTest;
-                                }
-                                Some(1) => {
-                                    app.state = State::Chart;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    KeyCode::Char('b') => {
-                        app.state = State::MainMenu;
-                    }
-                    _ => {}
-                },
-                InputMode::Typing => match key.code {
-                    KeyCode::Char(c) => {
-                        if c.is_whitespace() {
-                            app.text_input.clear()
-                        } else {
-                            app.text_input.push(c);
-                        }
-                    }
-                    KeyCode::Backspace => {
-                        let input = app.text_input.drain(..).collect::<String>();
+        if let Event::Key(key) = event {
+            app.notice = None;
+
+            // Errors show as a toast that doesn't take keys, only Esc closes it and only
+            // while Esc isn't pausing a test
+            let typing = app.input_mode == InputMode::Typing;
+            if !app.errors.is_empty() && key.code == KeyCode::Esc && !typing {
+                app.dismiss_error();
+                continue;
+            }
+
+            if app.show_help {
+                app.show_help = false;
+                continue;
+            }
+
+            // Naming or confirming a profile change takes every key until it is done
+            if app.profile_edit.is_some() {
+                let deleting = matches!(app.profile_edit, Some(ProfileEdit::Delete(_)));
+                match key.code {
+                    KeyCode::Char('y') if deleting => app.confirm_profile_edit(),
+                    KeyCode::Enter => app.confirm_profile_edit(),
+                    KeyCode::Char(c) if app.profile_edit_input(Some(c)) => {}
+                    KeyCode::Backspace if app.profile_edit_input(None) => {}
+                    _ => app.profile_edit = None,
+                }
+                continue;
+            }
+
+            // So does the history filter, tags or a deletion waiting to be confirmed
+            if app.history_edit.is_some() {
+                let deleting = app.history_edit == Some(HistoryEdit::Delete);
+                match key.code {
+                    KeyCode::Char('y') if deleting => app.confirm_history_edit(),
+                    KeyCode::Enter => app.confirm_history_edit(),
+                    KeyCode::Char(c) if app.history_edit_input(Some(c)) => {}
+                    KeyCode::Backspace if app.history_edit_input(None) => {}
+                    _ => app.cancel_history_edit(),
+                }
+                continue;
+            }
 
-                        let current_word = app.words.pop_front().unwrap();
+            let action = app.keymap.action(app.screen(), &key);
 
-                        if input == current_word {
-                            app.words.push_back(current_word);
-                            app.correct_words += 1;
-                        } else {
-                            app.words.push_back(current_word);
-                            app.incorrect_words += 1;
+            if app.screen() == Screen::Timer {
+                match action {
+                    Some(action) if app.timer_action(action) => continue,
+                    None => {
+                        if let KeyCode::Char(c) = key.code {
+                            app.timer_digit(c);
                         }
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
 
-                        app.text_input.pop();
+            match action {
+                Some(action) => {
+                    if perform(terminal, app, action)? {
+                        break;
                     }
-                    KeyCode::Esc => {
-                        app.input_mode = InputMode::Normal;
+                }
+                None => {
+                    if let InputMode::Typing = app.input_mode {
+                        match key.code {
+                            KeyCode::Char(c) => {
+                                app.type_char(c);
+                            }
+                            KeyCode::Backspace => {
+                                app.backspace();
+                            }
+                            _ => {}
+                        }
                     }
-                    _ => {}
                 }
-                _ => {}
             }
         }
     }
     Ok(())
 }
 
+/// Does what `action` stands for on the current screen, returning true when the app should
+/// quit.
+fn perform<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    action: Action,
+) -> Result<bool, ErrorKind> {
+    if app.state == State::History && app.history_action(action) {
+        return Ok(false);
+    }
+    match action {
+        Action::Quit => return Ok(true),
+        Action::Help => {
+            app.show_help = true;
+        }
+        Action::Suspend => {
+            suspend(terminal, app)?;
+        }
+        Action::Up => {
+            app.up();
+        }
+        Action::Down => {
+            app.down();
+        }
+        Action::Left => {
+            app.cycle_setting(false);
+        }
+        Action::Right => {
+            app.cycle_setting(true);
+        }
+        Action::Select if app.state == State::Profiles => {
+            app.select_profile();
+        }
+        Action::Select => match app.table_state.selected() {
+            Some(0) => {
+                app.state = State::TypingTest;
+            }
+            Some(1) => {
+                app.state = State::FocusTimer;
+            }
+            Some(2) => {
+                app.state = State::Chart;
+            }
+            Some(3) => {
+                app.open_history();
+            }
+            Some(4) => {
+                app.open_stats();
+            }
+            Some(5) => {
+                app.state = State::Settings;
+            }
+            Some(6) => {
+                app.state = State::Profiles;
+            }
+            _ => {}
+        },
+        Action::Back => {
+            if app.state == State::TypingTest {
+                app.abandon_test();
+            }
+            app.state = State::MainMenu;
+        }
+        Action::OpenSettings => {
+            app.state = State::Settings;
+        }
+        Action::StartTyping => {
+            app.start_typing();
+        }
+        Action::PauseTest => {
+            app.pause_test();
+        }
+        Action::RestartTest => {
+            app.restart_test(false);
+            app.start_typing();
+        }
+        Action::NextTest => {
+            app.restart_test(true);
+            app.start_typing();
+        }
+        Action::AbandonTest => {
+            app.abandon_test();
+        }
+        Action::EditTimer => {
+            app.edit_test_timer();
+        }
+        Action::ToggleTimer | Action::ResetTimer => {}
+        Action::ToggleChartFilter => {
+            app.toggle_chart_filter();
+        }
+        Action::Export => {
+            app.export_history();
+        }
+        Action::NewProfile | Action::RenameProfile | Action::DeleteProfile => {
+            app.start_profile_edit(action);
+        }
+        // Only used on the history screen
+        Action::SortHistory
+        | Action::ReverseHistory
+        | Action::FilterHistory
+        | Action::FilterMode
+        | Action::FilterDates
+        | Action::ReplayResult
+        | Action::TagResult
+        | Action::DeleteResult => {}
+    }
+    Ok(false)
+}
+
+/// Stops the process until the shell continues it, pausing a running test meanwhile,
+/// then repaints the whole screen.
+fn suspend<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), ErrorKind> {
+    if app.input_mode == InputMode::Typing {
+        app.pause_test();
+    }
+    terminal::suspend()?;
+    terminal.clear()?;
+    Ok(())
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -119,82 +318,211 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-fn error<B: Backend>(f: &mut Frame<B>, error: &ErrorKind) {
+/// The error at the front of the queue as a toast in the top right corner, which leaves the
+/// rest of the screen working under it. Returns where it was drawn.
+fn error_toast<B: Backend>(f: &mut Frame<B>, app: &App, error: &ErrorKind) -> Rect {
+    let title = match app.errors.len() {
+        1 => "An error occured!".to_string(),
+        n => format!("An error occured! (1 of {})", n),
+    };
     let block = Block::default()
-        .title(format!("An error occured!"))
+        .title(title)
         .borders(Borders::ALL)
-        .style(Style::default().bg(Rgb(0, 0, 0)).fg(Rgb(255, 0, 0)));
-    let text = vec![Spans::from(format!("Error: {}", error))];
-    let paragraph = Paragraph::new(text)
-        .block(block.clone())
-        .alignment(Alignment::Center);
-    let area = centered_rect(60, 20, f.size());
+        .style(Style::default().bg(app.theme.background).fg(app.theme.incorrect));
+    let text = format!("Error: {}", error);
+    let hint = match app.input_mode {
+        InputMode::Typing => "closes by itself",
+        _ => "esc: dismiss",
+    };
+
+    // As wide as the message up to half the screen, as tall as it wraps to
+    let size = f.size();
+    let width = (text.width() as u16 + 2).clamp(24, (size.width / 2).max(24)).min(size.width);
+    let inner_width = width.saturating_sub(2).max(1) as usize;
+    // Wrapping at word ends can take a row more than the width alone would
+    let rows = text.width().div_ceil(inner_width) + usize::from(text.width() > inner_width);
+    let rows = rows.clamp(1, 5) as u16;
+    let height = (rows + 3).min(size.height);
+    let area = Rect::new(size.right().saturating_sub(width), size.y, width, height);
+
+    let paragraph = Paragraph::new(vec![
+        Spans::from(text),
+        Spans::from(Span::styled(hint, Style::default().fg(app.theme.sub))),
+    ])
+    .block(block)
+    .wrap(Wrap { trim: true });
     f.render_widget(Clear, area);
     f.render_widget(paragraph, area);
-    f.render_widget(block, area);
+    area
 }
 
 pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    match app.state {
-        State::TypingTest => {
+    let theme = app.theme.clone();
+    let size = SizeClass::of(f.size());
 
-            match app.typing_test_state {
-                TypingTestState::End => {
-                    println!("ENDED");
-                }
-                _ => {}
-            }
+    f.render_widget(
+        Block::default().style(Style::default().bg(theme.background).fg(theme.text)),
+        f.size(),
+    );
+
+    if size == SizeClass::TooSmall {
+        let area = f.size();
+        let text = vec![
+            Spans::from(Span::styled("Terminal too small", Style::default().fg(theme.incorrect))),
+            Spans::from(Span::styled(
+                format!("{}x{}, needs {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT),
+                Style::default().fg(theme.sub),
+            )),
+        ];
+        let top = area.height.saturating_sub(text.len() as u16) / 2;
+        f.render_widget(
+            Paragraph::new(text).alignment(Alignment::Center).wrap(Wrap { trim: true }),
+            Rect::new(area.x, area.y + top, area.width, area.height - top),
+        );
+        return;
+    }
 
+    // Only what this draw puts on screen can be clicked
+    app.mouse_areas.table = None;
+    app.mouse_areas.bars = None;
+    app.mouse_areas.toast = None;
+
+    match app.state {
+        State::TypingTest => {
+            // Focus mode leaves nothing but the words while the clock runs
+            let focused = app.config.focus_mode
+                && app.input_mode == InputMode::Typing
+                && app.engine.state == TypingTestState::Running;
+            let constraints = match focused {
+                true => vec![Constraint::Min(2)],
+                false => vec![Constraint::Min(2), Constraint::Length(2), Constraint::Length(3)],
+            };
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(2)
-                .constraints(
-                    [
-                        Constraint::Min(2),
-                        Constraint::Length(2),
-                        Constraint::Length(3),
-                    ]
-                    .as_ref(),
-                )
+                .margin(size.margin(2))
+                .constraints(constraints)
                 .split(f.size());
 
-            let wrapper = Block::default()
-                .borders(Borders::ALL)
-                .style(Style::default().fg(Rgb(255, 255, 255)))
-                .title_alignment(Alignment::Center)
-                .title(Spans::from(Span::styled(
-                    "Bunbuntype",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )));
+            let wrapper = match focused {
+                true => Block::default().style(Style::default().fg(theme.text)),
+                false => Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(theme.text))
+                    .title_alignment(Alignment::Center)
+                    .title(Spans::from(Span::styled(
+                        "Bunbuntype",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ))),
+            };
 
+            // Right to left words are laid out from the right edge of the box
+            let direction = app.language.direction;
+            let alignment = match direction {
+                LanguageDirection::Ltr => Alignment::Left,
+                LanguageDirection::Rtl => Alignment::Right,
+            };
+            let mut cursor = None;
+            let (body, alignment) = match app.engine.state {
+                TypingTestState::End => (results_text(app), Alignment::Left),
+                TypingTestState::Abandoned => (
+                    vec![Spans::from(Span::styled(
+                        "Test abandoned, nothing was recorded.",
+                        Style::default().fg(theme.sub),
+                    ))],
+                    Alignment::Left,
+                ),
+                TypingTestState::Failed => (
+                    vec![Spans::from(Span::styled(
+                        format!(
+                            "Test failed, {}. Nothing was recorded.",
+                            app.engine.difficulty.level.failure()
+                        ),
+                        Style::default().fg(theme.incorrect),
+                    ))],
+                    Alignment::Left,
+                ),
+                _ => {
+                    let inner = wrapper.inner(chunks[0]);
+                    let (lines, caret) =
+                        word_lines(app, inner.width as usize, inner.height as usize);
+                    cursor = caret.map(|(row, column)| {
+                        (inner.x + column as u16, inner.y + row as u16)
+                    });
+                    (lines, alignment)
+                }
+            };
+            let inner = wrapper.inner(chunks[0]);
             f.render_widget(wrapper, chunks[0]);
+            let map = match app.engine.state {
+                TypingTestState::End => word_map_lines(app, inner.width as usize),
+                _ => Vec::new(),
+            };
+            match &app.last_result {
+                Some(result) if !result.timeline.is_empty() && inner.height > 10 => {
+                    // The map takes what the chart can spare, with a blank line above it
+                    let rows = match map.len() {
+                        0 => 0,
+                        rows => (rows + 1).min(inner.height as usize - 11),
+                    };
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [
+                                Constraint::Length(7),
+                                Constraint::Length(rows as u16),
+                                Constraint::Min(4),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(inner);
+                    f.render_widget(Paragraph::new(body), parts[0]);
+                    let map = std::iter::once(Spans::default()).chain(map).collect::<Vec<_>>();
+                    f.render_widget(Paragraph::new(map).alignment(alignment), parts[1]);
+                    timeline_chart(f, app, &result.timeline, parts[2]);
+                }
+                _ if !map.is_empty() => {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints(
+                            [Constraint::Length(body.len() as u16 + 1), Constraint::Min(1)]
+                                .as_ref(),
+                        )
+                        .split(inner);
+                    f.render_widget(Paragraph::new(body), parts[0]);
+                    f.render_widget(Paragraph::new(map).alignment(alignment), parts[1]);
+                }
+                _ => f.render_widget(Paragraph::new(body).alignment(alignment), inner),
+            }
 
-            let input = Paragraph::new(app.text_input.as_ref())
-                .style(match app.input_mode {
-                    InputMode::Normal => Style::default().fg(Color::Red),
-                    InputMode::Typing => Style::default().fg(Color::Blue),
-                    _ => Style::default().fg(Color::Green),
-                })
-                .block(Block::default().borders(Borders::ALL).title(Span::styled(
-                    "Input",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )));
-            f.render_widget(input, chunks[2]);
-            match app.input_mode {
-                InputMode::Normal => {}
+            // A line caret is the terminal cursor, placed in the words
+            if let Some((x, y)) = cursor {
+                f.set_cursor(x, y);
+            }
+            if !focused {
+                let status = Paragraph::new(vec![
+                    test_progress(app),
+                    Spans::from(Span::styled(test_hint(app), Style::default().fg(theme.sub))),
+                ]);
+                f.render_widget(status, chunks[1]);
 
-                InputMode::Typing => f.set_cursor(
-                    chunks[2].x + app.text_input.width() as u16 + 1,
-                    chunks[2].y + 1,
-                ),
-                _ => {}
+                let input = Paragraph::new(direction.visual(&app.engine.input))
+                    .alignment(alignment)
+                    .style(match app.input_mode {
+                        InputMode::Normal => Style::default().fg(theme.sub),
+                        InputMode::Typing => Style::default().fg(theme.accent),
+                    })
+                    .block(Block::default().borders(Borders::ALL).title(Span::styled(
+                        "Input",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )));
+                f.render_widget(input, chunks[2]);
             }
         }
 
         State::MainMenu => {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(3)
+                .margin(size.margin(3))
                 .constraints(
                     [
                         Constraint::Min(1),
@@ -207,18 +535,21 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
 
             let wrapper = Block::default()
                 .borders(Borders::ALL)
-                .style(Style::default().fg(Rgb(255, 255, 255)))
+                .style(Style::default().fg(theme.text))
                 .title_alignment(Alignment::Center)
-                .title(Spans::from(Span::styled(
-                    "Bunbuntype",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )));
+                .title(Spans::from(vec![
+                    Span::styled("Bunbuntype", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::styled(
+                        format!(" ({})", app.profile.name),
+                        Style::default().fg(theme.sub),
+                    ),
+                ]));
 
             let selected_style = Style::default().add_modifier(Modifier::REVERSED);
-            let normal_style = Style::default().bg(Rgb(144, 238, 144));
+            let normal_style = Style::default().bg(theme.accent);
             let header_cells = ["Select Menu"]
                 .iter()
-                .map(|h| Cell::from(*h).style(Style::default().fg(Rgb(0, 0, 0))));
+                .map(|h| Cell::from(*h).style(Style::default().fg(theme.background)));
             let header = Row::new(header_cells)
                 .style(normal_style)
                 .height(1)
@@ -235,7 +566,6 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
             });
             let t = Table::new(rows)
                 .header(header)
-                .block(wrapper)
                 .highlight_style(selected_style)
                 .highlight_symbol(">> ")
                 .widths(&[
@@ -243,21 +573,1308 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                     Constraint::Length(30),
                     Constraint::Min(10),
                 ]);
-            f.render_stateful_widget(t, chunks[0], &mut app.table_state);
+            let inner = wrapper.inner(chunks[0]);
+            f.render_widget(wrapper, chunks[0]);
+            // The practice panel is left out when it would squeeze the menu
+            let practice_width = if size.is_compact() { 0 } else { 60 };
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    [
+                        Constraint::Percentage(100 - practice_width),
+                        Constraint::Percentage(practice_width),
+                    ]
+                    .as_ref(),
+                )
+                .split(inner);
+            from_top(&mut app.table_state);
+            f.render_stateful_widget(t, columns[0], &mut app.table_state);
+            let selected = app.table_state.selected();
+            app.mouse_areas.table =
+                Some(TableArea::new(columns[0], 2, app.items.len(), 2, selected));
+
+            if !size.is_compact() {
+                let practice = Block::default()
+                    .borders(Borders::LEFT)
+                    .style(Style::default().fg(theme.text))
+                    .title(Span::styled("Practice", Style::default().add_modifier(Modifier::BOLD)));
+                let lines = practice_lines(app, practice.inner(columns[1]).width as usize);
+                f.render_widget(Paragraph::new(lines).block(practice), columns[1]);
+            }
         }
         State::Chart => {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(2)
-                .constraints([Constraint::Percentage(100), Constraint::Percentage(100)].as_ref())
+                .margin(size.margin(2))
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
                 .split(f.size());
+            let block = Block::default()
+                .title(format!("WPM Data ({})", app.chart_filter.label()))
+                .borders(Borders::ALL);
+            // tui draws as many of the first bars as fit
+            let area = block.inner(chunks[0]);
+            let bars = BarsArea {
+                area,
+                bar_width: CHART_BAR_WIDTH,
+                bar_gap: 1,
+                first: app.chart_start(),
+                len: app.chart_data().len().min((area.width / (CHART_BAR_WIDTH + 1)) as usize),
+            };
             let barchart = BarChart::default()
-                .block(Block::default().title("WPM Data").borders(Borders::ALL))
-                .data(&app.wpm_results)
-                .bar_width(9)
-                .bar_style(Style::default().fg(Color::Yellow))
-                .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+                .block(block)
+                .data(app.chart_data())
+                .bar_width(bars.bar_width)
+                .bar_gap(bars.bar_gap)
+                .bar_style(Style::default().fg(theme.chart_bar))
+                .value_style(Style::default().fg(theme.chart_value).bg(theme.chart_bar));
             f.render_widget(barchart, chunks[0]);
+            app.mouse_areas.bars = Some(bars);
+            if let Some((column, row)) = app.mouse_areas.hover {
+                if let Some(result) = bars.bar_at(column, row).and_then(|i| app.results.get(i)) {
+                    result_tooltip(f, app, result, (column, row));
+                }
+            }
+
+            let hints = action_hints(
+                app,
+                Screen::Chart,
+                &[(Action::ToggleChartFilter, "filter"), (Action::Export, "export")],
+            );
+            let help = Paragraph::new(hints)
+                .style(Style::default().fg(theme.sub))
+                .alignment(Alignment::Center);
+            f.render_widget(help, chunks[1]);
+        }
+        State::Stats => stats_dashboard(f, app),
+        State::History => history_table(f, app),
+        State::FocusTimer => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(size.margin(3))
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .split(f.size());
+
+            let wrapper = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.text))
+                .title_alignment(Alignment::Center)
+                .title(Spans::from(Span::styled(
+                    "Focus Timer",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+            let inner = wrapper.inner(chunks[0]);
+            f.render_widget(wrapper, chunks[0]);
+
+            let mut lines = vec![timer_spans(app, &app.focus_timer)];
+            if app.focus_timer.state() == TimerViewState::Finished {
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled(
+                    "Time is up, take a break!",
+                    Style::default().fg(theme.text),
+                )));
+            }
+            let top = inner.height.saturating_sub(lines.len() as u16) / 2;
+            let timer = Paragraph::new(lines).alignment(Alignment::Center);
+            f.render_widget(
+                timer,
+                Rect::new(inner.x, inner.y + top, inner.width, inner.height - top),
+            );
+
+            let hints: &[(Action, &str)] = match app.focus_timer.state() {
+                TimerViewState::Config => &[
+                    (Action::Left, "previous field"),
+                    (Action::Right, "next field"),
+                    (Action::Up, "more"),
+                    (Action::Down, "less"),
+                    (Action::Select, "start"),
+                ],
+                TimerViewState::Running | TimerViewState::Paused => {
+                    &[(Action::ToggleTimer, "pause/resume"), (Action::ResetTimer, "reset")]
+                }
+                TimerViewState::Finished => &[(Action::Select, "again"), (Action::Back, "menu")],
+            };
+            let help = Paragraph::new(action_hints(app, Screen::Timer, hints))
+                .style(Style::default().fg(theme.sub))
+                .alignment(Alignment::Center);
+            f.render_widget(help, chunks[1]);
+        }
+        State::Settings => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(size.margin(3))
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .split(f.size());
+
+            let wrapper = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.text))
+                .title_alignment(Alignment::Center)
+                .title(Spans::from(Span::styled(
+                    "Settings",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+
+            let header = Row::new(["Setting", "Value"]
+                .iter()
+                .map(|h| Cell::from(*h).style(Style::default().fg(theme.background))))
+                .style(Style::default().bg(theme.accent))
+                .height(1)
+                .bottom_margin(1);
+            let rows = Setting::ALL.iter().map(|setting| {
+                Row::new(vec![
+                    Cell::from(setting.label()),
+                    Cell::from(format!("< {} >", app.setting_value(*setting)))
+                        .style(Style::default().fg(theme.accent)),
+                ])
+                .bottom_margin(1)
+            });
+            let t = Table::new(rows)
+                .header(header)
+                .block(wrapper)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol(">> ")
+                .widths(&SETTINGS_WIDTHS);
+            let rows = wrapper_inner(chunks[0]);
+            from_top(&mut app.settings_state);
+            f.render_stateful_widget(t, chunks[0], &mut app.settings_state);
+            let selected = app.settings_state.selected();
+            app.mouse_areas.table = Some(
+                TableArea::new(rows, 2, Setting::ALL.len(), 2, selected)
+                    .with_value(&SETTINGS_WIDTHS, selected.is_some()),
+            );
+
+            let help = Paragraph::new(help_hint(app))
+                .style(Style::default().fg(theme.sub))
+                .alignment(Alignment::Center);
+            f.render_widget(help, chunks[1]);
+        }
+        State::Profiles => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(size.margin(3))
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .split(f.size());
+
+            let wrapper = Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(theme.text))
+                .title_alignment(Alignment::Center)
+                .title(Spans::from(Span::styled(
+                    "Profiles",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+
+            let rows = app.profile_names.iter().map(|name| {
+                let current = match *name == app.profile.name {
+                    true => "in use",
+                    false => "",
+                };
+                Row::new(vec![
+                    Cell::from(name.as_str()),
+                    Cell::from(current).style(Style::default().fg(theme.sub)),
+                ])
+                .bottom_margin(1)
+            });
+            let t = Table::new(rows)
+                .block(wrapper)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol(">> ")
+                .widths(&[Constraint::Percentage(60), Constraint::Percentage(40)]);
+            let rows = wrapper_inner(chunks[0]);
+            from_top(&mut app.profiles_state);
+            f.render_stateful_widget(t, chunks[0], &mut app.profiles_state);
+            let selected = app.profiles_state.selected();
+            app.mouse_areas.table =
+                Some(TableArea::new(rows, 0, app.profile_names.len(), 2, selected));
+
+            let (prompt, style) = match &app.profile_edit {
+                Some(ProfileEdit::Create(name)) => {
+                    (format!("New profile name: {}_", name), Style::default().fg(theme.accent))
+                }
+                Some(ProfileEdit::Rename { from, to }) => {
+                    (format!("Rename {} to: {}_", from, to), Style::default().fg(theme.accent))
+                }
+                Some(ProfileEdit::Delete(name)) => (
+                    format!("Delete {} and its history? y to confirm", name),
+                    Style::default().fg(theme.incorrect),
+                ),
+                None => (
+                    action_hints(
+                        app,
+                        Screen::Profiles,
+                        &[
+                            (Action::Select, "switch"),
+                            (Action::NewProfile, "new"),
+                            (Action::RenameProfile, "rename"),
+                            (Action::DeleteProfile, "delete"),
+                        ],
+                    ),
+                    Style::default().fg(theme.sub),
+                ),
+            };
+            let help = Paragraph::new(prompt).style(style).alignment(Alignment::Center);
+            f.render_widget(help, chunks[1]);
+        }
+    }
+
+    if let Some(notice) = &app.notice {
+        let size = f.size();
+        let line = Rect::new(size.x, size.bottom().saturating_sub(1), size.width, 1.min(size.height));
+        let notice = Paragraph::new(notice.as_str())
+            .style(Style::default().fg(theme.accent))
+            .alignment(Alignment::Center);
+        f.render_widget(notice, line);
+    }
+
+    if app.show_help {
+        help_overlay(f, app);
+    }
+
+    if let Some(e) = app.errors.front() {
+        app.mouse_areas.toast = Some(error_toast(f, app, e));
+    }
+}
+
+/// Widths of the history table columns, in the order of `Column::ALL` followed by the tags.
+const HISTORY_WIDTHS: [Constraint; 7] = [
+    Constraint::Length(16),
+    Constraint::Length(9),
+    Constraint::Length(5),
+    Constraint::Length(8),
+    Constraint::Length(8),
+    Constraint::Length(10),
+    Constraint::Min(4),
+];
+
+fn history_table<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let theme = app.theme.clone();
+    let size = SizeClass::of(f.size());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(size.margin(2))
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(f.size());
+
+    let view = &app.history_view;
+    let mut title = format!("History ({} of {})", view.rows.len(), app.results.len());
+    if !view.filter.is_empty() {
+        title.push_str(&format!(" [{}]", view.filter.describe()));
+    }
+    let wrapper = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.text))
+        .title(Span::styled(title, Style::default().add_modifier(Modifier::BOLD)));
+
+    let arrow = if view.descending { " ▼" } else { " ▲" };
+    let header = Column::ALL
+        .iter()
+        .map(|column| match *column == view.column {
+            true => format!("{}{}", column.label(), arrow),
+            false => column.label().to_string(),
+        })
+        .chain(["Tags".to_string()])
+        .map(|h| Cell::from(h).style(Style::default().fg(theme.background)));
+    let header = Row::new(header)
+        .style(Style::default().bg(theme.accent))
+        .height(1)
+        .bottom_margin(1);
+    let rows = view.rows.iter().map(|i| {
+        let result = &app.results[*i];
+        // Flagged results are kept but do not count as bests
+        let style = match result.is_valid() {
+            true => Style::default(),
+            false => Style::default().fg(theme.sub),
+        };
+        Row::new(vec![
+            Cell::from(result.date_time.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::from(result.mode.map_or("-".to_string(), |m| m.to_string())),
+            Cell::from(format!("{:.0}", result.wpm)).style(Style::default().fg(theme.accent)),
+            Cell::from(format!("{:.1}%", result.accuracy * 100.0)),
+            Cell::from(format!("{:.0}s", result.duration * 60.0)),
+            Cell::from(result.language.clone().unwrap_or_else(|| "-".to_string())),
+            Cell::from(result.tags.join(", ")).style(Style::default().fg(theme.sub)),
+        ])
+        .style(style)
+    });
+    let empty = match (app.results.is_empty(), view.rows.is_empty()) {
+        (true, _) => Some("No results yet, finish a test to see it here."),
+        (false, true) => Some("No results match the filter."),
+        _ => None,
+    };
+    let t = Table::new(rows)
+        .header(header)
+        .block(wrapper)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ")
+        .widths(&HISTORY_WIDTHS);
+    let inner = wrapper_inner(chunks[0]);
+    from_top(&mut app.history_state);
+    f.render_stateful_widget(t, chunks[0], &mut app.history_state);
+    if let Some(empty) = empty {
+        let area = Rect::new(inner.x, inner.y + 2, inner.width, inner.height.saturating_sub(2));
+        f.render_widget(Paragraph::new(empty).style(Style::default().fg(theme.sub)), area);
+    }
+
+    let (prompt, style) = match &app.history_edit {
+        Some(HistoryEdit::Filter) => (
+            format!("Filter: {}_", app.history_view.filter.text),
+            Style::default().fg(theme.accent),
+        ),
+        Some(HistoryEdit::Tag(tags)) => {
+            (format!("Tags, separated by commas: {}_", tags), Style::default().fg(theme.accent))
+        }
+        Some(HistoryEdit::Delete) => (
+            "Delete this result and its keystrokes? y to confirm".to_string(),
+            Style::default().fg(theme.incorrect),
+        ),
+        None if app.history_detail => (
+            action_hints(
+                app,
+                Screen::History,
+                &[
+                    (Action::ReplayResult, "replay"),
+                    (Action::TagResult, "tag"),
+                    (Action::DeleteResult, "delete"),
+                    (Action::Back, "close"),
+                ],
+            ),
+            Style::default().fg(theme.sub),
+        ),
+        None => (
+            action_hints(
+                app,
+                Screen::History,
+                &[
+                    (Action::Select, "open"),
+                    (Action::SortHistory, "sort"),
+                    (Action::ReverseHistory, "reverse"),
+                    (Action::FilterHistory, "filter"),
+                    (Action::FilterMode, "mode"),
+                    (Action::FilterDates, "dates"),
+                ],
+            ),
+            Style::default().fg(theme.sub),
+        ),
+    };
+    f.render_widget(Paragraph::new(prompt).style(style).alignment(Alignment::Center), chunks[1]);
+
+    match app.selected_result() {
+        Some(index) if app.history_detail => result_detail(f, app, &app.results[index]),
+        _ => {
+            let selected = app.history_state.selected();
+            let len = app.history_view.rows.len();
+            app.mouse_areas.table = Some(TableArea::new(inner, 2, len, 1, selected));
+        }
+    }
+}
+
+/// Everything saved about a result, with the text typed so far while it is replayed.
+fn result_detail<B: Backend>(f: &mut Frame<B>, app: &App, result: &WpmResult) {
+    let theme = &app.theme;
+    let label = |text: &str| Span::styled(format!("{:<10}", text), Style::default().fg(theme.sub));
+    let line = |name: &str, value: String| Spans::from(vec![label(name), Span::raw(value)]);
+
+    let mut lines = vec![
+        line("Date", result.date_time.format("%Y-%m-%d %H:%M:%S").to_string()),
+        line("Mode", result.mode.map_or("-".to_string(), |m| m.to_string())),
+        line("Speed", format!("{:.1} WPM, {:.1} adjusted", result.wpm, result.awpm)),
+        line("Accuracy", format!("{:.1}%", result.accuracy * 100.0)),
+        line("Duration", format!("{:.1} seconds", result.duration * 60.0)),
+        line("Language", result.language.clone().unwrap_or_else(|| "-".to_string())),
+        line("Layout", result.layout.clone().unwrap_or_else(|| "-".to_string())),
+        line("Rules", result.difficulty.to_string()),
+    ];
+    if let Some(source) = &result.source {
+        lines.push(line("Source", source.clone()));
+    }
+    if !result.tags.is_empty() {
+        lines.push(line("Tags", result.tags.join(", ")));
+    }
+    let flags = result.flags.iter().map(|f| f.description()).collect::<Vec<_>>();
+    lines.push(match flags.is_empty() {
+        true => line("Status", "valid".to_string()),
+        false => Spans::from(vec![
+            label("Status"),
+            Span::styled(
+                format!("flagged, {}", flags.join(", ")),
+                Style::default().fg(theme.incorrect),
+            ),
+        ]),
+    });
+
+    if let Some(playback) = &app.playback {
+        let now = Instant::now();
+        let status = match playback.is_done_at(now) {
+            true => "Replay finished",
+            false => "Replaying",
+        };
+        lines.push(Spans::default());
+        lines.push(Spans::from(Span::styled(status, Style::default().fg(theme.sub))));
+        let mut typed = vec![Span::styled(playback.typed_at(now), Style::default().fg(theme.text))];
+        if !playback.is_done_at(now) {
+            typed.push(Span::styled("_", Style::default().fg(theme.accent)));
+        }
+        lines.push(Spans::from(typed));
+    }
+
+    let detail = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(Span::styled("Result", Style::default().add_modifier(Modifier::BOLD)))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(theme.background).fg(theme.text)),
+        );
+    let area = centered_rect(70, 70, f.size());
+    f.render_widget(Clear, area);
+    f.render_widget(detail, area);
+}
+
+/// Columns of the settings table, the value last so clicking it cycles it.
+const SETTINGS_WIDTHS: [Constraint; 2] = [Constraint::Percentage(40), Constraint::Percentage(60)];
+const CHART_BAR_WIDTH: u16 = 9;
+
+/// Inside of a bordered block drawn in `area`.
+fn wrapper_inner(area: Rect) -> Rect {
+    Block::default().borders(Borders::ALL).inner(area)
+}
+
+/// Details of the result under the mouse on the chart, next to the pointer and kept on
+/// screen.
+fn result_tooltip<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    result: &WpmResult,
+    (column, row): (u16, u16),
+) {
+    let theme = &app.theme;
+    let mut lines = vec![
+        result.date_time.format("%Y-%m-%d %H:%M").to_string(),
+        format!("{:.0} WPM, {:.0} adjusted", result.wpm, result.awpm),
+        format!("{:.1}% accuracy", result.accuracy * 100.0),
+        format!("{:.0} seconds", result.duration * 60.0),
+    ];
+    if let Some(mode) = &result.mode {
+        lines.push(format!("Mode: {}", mode));
+    }
+    if let Some(language) = &result.language {
+        lines.push(format!("Language: {}", language));
+    }
+    if !result.is_valid() {
+        lines.push("Flagged".to_string());
+    }
+
+    let screen = f.size();
+    let width = (lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 2).min(screen.width);
+    let height = (lines.len() as u16 + 2).min(screen.height);
+    let x = match column + 2 + width <= screen.right() {
+        true => column + 2,
+        false => column.saturating_sub(width + 1),
+    };
+    let y = row.min(screen.bottom().saturating_sub(height));
+    let area = Rect::new(x.max(screen.x), y, width, height);
+
+    let text = lines.into_iter().map(Spans::from).collect::<Vec<_>>();
+    let tooltip = Paragraph::new(text).style(Style::default().fg(theme.text)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default().bg(theme.background).fg(theme.accent)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(tooltip, area);
+}
+
+/// Width in WPM of the bars of the dashboard histogram.
+const HISTOGRAM_BUCKET: u64 = 10;
+
+fn stats_dashboard<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let theme = &app.theme;
+    let size = SizeClass::of(f.size());
+    let stats = &app.stats;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(size.margin(2))
+        .constraints(
+            [
+                Constraint::Length(4),
+                Constraint::Min(8),
+                Constraint::Length(5),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+    let block = |title: &str| {
+        Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default().fg(theme.text))
+            .title(Span::styled(title.to_string(), Style::default().add_modifier(Modifier::BOLD)))
+    };
+    let label = |text: &str| Span::styled(format!("{} ", text), Style::default().fg(theme.sub));
+    let value = |text: String| Span::styled(format!("{}   ", text), Style::default().fg(theme.accent));
+
+    let help = Paragraph::new(help_hint(app))
+        .style(Style::default().fg(theme.sub))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[3]);
+
+    let total = &stats.total;
+    if total.tests == 0 {
+        let empty = Paragraph::new("No results yet, finish a test to see statistics.")
+            .style(Style::default().fg(theme.sub))
+            .block(block("Statistics"));
+        f.render_widget(empty, chunks[0]);
+        return;
+    }
+
+    let minutes = total.total_duration.round() as u64;
+    let mut last = vec![label("Last test")];
+    if let (Some(result), Some(percentile)) = (&stats.last, stats.last_percentile) {
+        last.push(value(format!(
+            "{:.0} wpm, faster than {:.0}% of tests",
+            result.wpm, percentile
+        )));
+    }
+    if let Some(rate) = stats.weekly_improvement {
+        last.push(label("Improvement"));
+        last.push(value(format!("{:+.1} wpm per week", rate)));
+    }
+    let overview = Paragraph::new(vec![
+        Spans::from(vec![
+            label("Tests"),
+            value(total.tests.to_string()),
+            label("Time typed"),
+            value(format!("{}h {:02}m", minutes / 60, minutes % 60)),
+            label("Average"),
+            value(format!("{:.0} wpm", total.average_wpm())),
+            label("Best"),
+            value(format!("{:.0} wpm", total.best_wpm)),
+            label("Accuracy"),
+            value(format!("{:.1}%", total.average_accuracy() * 100.0)),
+        ]),
+        Spans::from(last),
+    ])
+    .block(block("Statistics"));
+    f.render_widget(overview, chunks[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[1]);
+
+    let header = Row::new(["Mode", "Tests", "Average", "Best"]
+        .iter()
+        .map(|h| Cell::from(*h).style(Style::default().fg(theme.background))))
+        .style(Style::default().bg(theme.accent))
+        .bottom_margin(1);
+    let rows = stats.by_mode.iter().map(|(mode, summary)| {
+        Row::new(vec![
+            Cell::from(mode.clone()),
+            Cell::from(summary.tests.to_string()),
+            Cell::from(format!("{:.0} wpm", summary.average_wpm())),
+            Cell::from(format!("{:.0} wpm", summary.best_wpm)),
+        ])
+    });
+    let modes = Table::new(rows).header(header).block(block("By mode")).widths(&[
+        Constraint::Percentage(34),
+        Constraint::Percentage(18),
+        Constraint::Percentage(24),
+        Constraint::Percentage(24),
+    ]);
+    f.render_widget(modes, columns[0]);
+
+    let histogram = stats.histogram(HISTOGRAM_BUCKET);
+    let labels = histogram.iter().map(|(wpm, _)| wpm.to_string()).collect::<Vec<_>>();
+    let data = labels
+        .iter()
+        .zip(&histogram)
+        .map(|(label, (_, count))| (label.as_str(), *count))
+        .collect::<Vec<_>>();
+    let distribution = BarChart::default()
+        .block(block("WPM distribution"))
+        .data(&data)
+        .bar_width(4)
+        .bar_style(Style::default().fg(theme.chart_bar))
+        .value_style(Style::default().fg(theme.chart_value).bg(theme.chart_bar));
+    f.render_widget(distribution, columns[1]);
+
+    // Sparklines start at zero, so bars rise from the tens of percent below the worst week
+    // to keep small changes visible, in tenths of a percent
+    let width = chunks[2].width.saturating_sub(2) as usize;
+    let weeks = stats.by_week.values().map(|w| w.average_accuracy()).collect::<Vec<_>>();
+    let weeks = &weeks[weeks.len().saturating_sub(width)..];
+    let floor = (weeks.iter().copied().fold(1.0, f64::min) * 10.0).floor() / 10.0;
+    let accuracy = weeks
+        .iter()
+        .map(|a| ((a - floor) * 1000.0).round() as u64 + 1)
+        .collect::<Vec<_>>();
+    let title = format!("Accuracy by week, {:.0}% to 100%", floor * 100.0);
+    let trend = Sparkline::default()
+        .block(block(&title))
+        .data(&accuracy)
+        .max(((1.0 - floor) * 1000.0).round() as u64 + 1)
+        .style(Style::default().fg(theme.accent));
+    f.render_widget(trend, chunks[2]);
+}
+
+/// The first key bound to `action` on `screen`, for use in hints.
+fn key_name(app: &App, screen: Screen, action: Action) -> Option<String> {
+    app.keymap
+        .bindings(screen)
+        .into_iter()
+        .find(|(a, _)| *a == action)
+        .and_then(|(_, chords)| chords.first().map(|c| c.to_string()))
+}
+
+/// A one line hint pointing at the help overlay, using whatever key is bound to it.
+fn help_hint(app: &App) -> String {
+    match key_name(app, app.screen(), Action::Help) {
+        Some(key) => format!("Press {} for key bindings", key),
+        None => String::new(),
+    }
+}
+
+/// Joins `(action, description)` pairs into a hint, skipping unbound actions.
+fn action_hints(app: &App, screen: Screen, actions: &[(Action, &str)]) -> String {
+    actions
+        .iter()
+        .filter_map(|(action, text)| key_name(app, screen, *action).map(|k| format!("{}: {}", k, text)))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// The test mode followed by the live stats bar.
+fn test_progress(app: &App) -> Spans<'static> {
+    let style = Style::default().fg(app.theme.accent);
+    let mut spans = vec![Span::styled(app.engine.mode.to_string(), style)];
+
+    // The duration being picked takes the place of the stats
+    if app.editing_timer {
+        spans.push(Span::styled(" | ", style));
+        spans.extend(timer_spans(app, &app.test_timer).0);
+    } else if app.config.live_stats {
+        let mut fields = app.config.stats_fields.clone();
+        if app.config.blind_mode {
+            fields.retain(|field| !field.reveals_mistakes());
+        }
+        // The countdown of a time test is the one the focus timer uses
+        if matches!(app.engine.mode, TestMode::Time(_)) && fields.contains(&StatField::Left) {
+            fields.retain(|field| *field != StatField::Left);
+            spans.push(Span::styled(" | ", style));
+            match app.test_timer.state() {
+                TimerViewState::Config => spans.push(Span::styled(app.test_timer.render(), style)),
+                _ => spans.extend(timer_spans(app, &app.test_timer).0),
+            }
+        }
+        for field in stats_bar(&fields, &app.live_stats) {
+            spans.push(Span::styled(" | ", style));
+            spans.push(Span::styled(field, style));
+        }
+    }
+
+    Spans::from(spans)
+}
+
+/// The countdown, with the field being edited highlighted while in the config state.
+fn timer_spans(app: &App, timer: &TimerView) -> Spans<'static> {
+    let theme = &app.theme;
+    let style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+
+    match timer.state() {
+        TimerViewState::Config => {
+            let text = timer.render();
+            let mut spans = Vec::new();
+            for (i, field) in text.split(':').enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(":", style));
+                }
+                let field_style = if i as u8 == timer.focus() {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style
+                };
+                spans.push(Span::styled(field.to_string(), field_style));
+            }
+            Spans::from(spans)
+        }
+        TimerViewState::Running => Spans::from(Span::styled(timer.render(), style)),
+        TimerViewState::Paused => Spans::from(vec![
+            Span::styled(timer.render(), style),
+            Span::styled(" (paused)", Style::default().fg(theme.sub)),
+        ]),
+        TimerViewState::Finished => Spans::from(Span::styled(
+            timer.render(),
+            Style::default().fg(theme.correct).add_modifier(Modifier::BOLD),
+        )),
+    }
+}
+
+fn test_hint(app: &App) -> String {
+    if app.editing_timer {
+        return format!(
+            "Type the duration  {}",
+            action_hints(
+                app,
+                Screen::Timer,
+                &[(Action::Left, "previous field"), (Action::Right, "next field"), (Action::Select, "confirm"), (Action::Back, "cancel")],
+            )
+        );
+    }
+
+    match (app.engine.state, &app.input_mode) {
+        (TypingTestState::NotStarted, InputMode::Typing) => "Start typing, the clock starts on the first key".to_string(),
+        (TypingTestState::Running, InputMode::Typing) => {
+            action_hints(app, Screen::Typing, &[(Action::PauseTest, "pause"), (Action::RestartTest, "restart")])
+        }
+        (TypingTestState::Paused, _) => format!(
+            "Paused  {}",
+            action_hints(
+                app,
+                Screen::Test,
+                &[
+                    (Action::StartTyping, "resume"),
+                    (Action::RestartTest, "restart"),
+                    (Action::NextTest, "new text"),
+                    (Action::AbandonTest, "abandon"),
+                ]
+            )
+        ),
+        (TypingTestState::End | TypingTestState::Abandoned | TypingTestState::Failed, _) => {
+            action_hints(
+                app,
+                Screen::Test,
+                &[
+                    (Action::RestartTest, "retry"),
+                    (Action::NextTest, "next test"),
+                    (Action::Back, "menu"),
+                ],
+            )
+        }
+        _ => action_hints(
+            app,
+            Screen::Test,
+            &[(Action::StartTyping, "start"), (Action::EditTimer, "set time"), (Action::Back, "menu")],
+        ),
+    }
+}
+
+fn results_text(app: &App) -> Vec<Spans<'static>> {
+    let theme = &app.theme;
+    let engine = &app.engine;
+    let result = match &app.last_result {
+        Some(result) => result,
+        None => return Vec::new(),
+    };
+
+    let line = |label: &str, value: String| {
+        Spans::from(vec![
+            Span::styled(format!("{:<12}", label), Style::default().fg(theme.sub)),
+            Span::styled(value, Style::default().fg(theme.text).add_modifier(Modifier::BOLD)),
+        ])
+    };
+
+    let mut lines = vec![
+        line("wpm", format!("{:.0}", result.wpm)),
+        line("accuracy", format!("{:.1}%", result.accuracy * 100.0)),
+        line("awpm", format!("{:.0}", result.awpm)),
+        line("time", format!("{:.1}s", result.duration * 60.0)),
+        line(
+            "words",
+            format!("{} correct, {} incorrect", engine.correct_words(), engine.incorrect_words()),
+        ),
+    ];
+    if !result.difficulty.is_default() {
+        lines.push(line("difficulty", result.difficulty.to_string()));
+    }
+    if !result.is_valid() {
+        let reasons = result.flags.iter().map(|f| f.description()).collect::<Vec<_>>();
+        lines.push(Spans::from(Span::styled(
+            format!("Flagged, not counted as a best: {}", reasons.join(", ")),
+            Style::default().fg(theme.incorrect),
+        )));
+    }
+    lines
+}
+
+/// The words of the test in lines of `width` columns, each colored by whether it was typed
+/// right. Blind mode leaves this as the first look at the mistakes.
+fn word_map_lines(app: &App, width: usize) -> Vec<Spans<'static>> {
+    let theme = &app.theme;
+    let map = app.engine.word_map();
+    if map.is_empty() {
+        return Vec::new();
+    }
+    let words = map.iter().map(|(word, _)| word.clone()).collect::<Vec<_>>();
+    let starts = line_starts(&words, width.max(1), false);
+    let cue = match app.config.text_cues {
+        true => Modifier::BOLD | Modifier::UNDERLINED,
+        false => Modifier::empty(),
+    };
+
+    let mut lines = Vec::new();
+    for (line, start) in starts.iter().enumerate() {
+        let end = starts.get(line + 1).copied().unwrap_or(map.len());
+        let mut spans = Vec::new();
+        for (word, correct) in &map[*start..end] {
+            let style = match correct {
+                true => Style::default().fg(theme.correct),
+                false => Style::default().fg(theme.incorrect).add_modifier(cue),
+            };
+            spans.push(Span::styled(app.language.direction.visual(word), style));
+            spans.push(Span::raw(" ".repeat(WORD_GAP)));
+        }
+        // Right to left the first word goes at the right edge
+        if app.language.direction == LanguageDirection::Rtl {
+            spans.reverse();
+        }
+        lines.push(Spans::from(spans));
+    }
+    lines
+}
+
+/// The daily goal, streak and a heatmap of the practice of the last weeks, as many as
+/// fit in `width` columns.
+fn practice_lines(app: &App, width: usize) -> Vec<Spans<'static>> {
+    let theme = &app.theme;
+    let practice = &app.practice;
+    let today = chrono::Local::now().date_naive();
+    let label = |text: &str| Span::styled(format!(" {:<8}", text), Style::default().fg(theme.sub));
+    let value = |text: String| Span::styled(text, Style::default().fg(theme.text));
+
+    let goal = app.config.daily_goal;
+    let progress = practice.goal_progress(goal, today);
+    let day = practice.day(today);
+    let done = match goal {
+        DailyGoal::Minutes(_) => format!(" {:.1} of {}", day.minutes, goal),
+        DailyGoal::Tests(_) => format!(" {} of {}", day.tests, goal),
+    };
+    let bar_width = 10;
+    let filled = (progress * bar_width as f64).round() as usize;
+    let goal_color = if progress >= 1.0 { theme.correct } else { theme.accent };
+    let mut lines = vec![
+        Spans::from(vec![
+            label("Today"),
+            Span::styled("█".repeat(filled), Style::default().fg(goal_color)),
+            Span::styled("░".repeat(bar_width - filled), Style::default().fg(theme.sub)),
+            value(done),
+        ]),
+        Spans::from(vec![
+            label("Streak"),
+            value(match practice.streak(today) {
+                1 => "1 day".to_string(),
+                days => format!("{} days", days),
+            }),
+        ]),
+        Spans::from(vec![
+            label("Best"),
+            value(practice.best_wpm().map_or("-".to_string(), |wpm| format!("{:.0} wpm", wpm))),
+        ]),
+        Spans::from(""),
+    ];
+
+    // Each week is a column two cells wide, after the weekday labels
+    let weeks = practice.weeks(today, (width.saturating_sub(5) / 2).clamp(1, 52));
+    for (weekday, name) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut spans = vec![Span::styled(format!(" {:<4}", name), Style::default().fg(theme.sub))];
+        for week in &weeks {
+            spans.push(match week[weekday] {
+                Some(day) if day.level() > 0 => {
+                    let cell = ["", "░", "▒", "▓", "█"][day.level()];
+                    Span::styled(format!("{} ", cell), Style::default().fg(theme.accent))
+                }
+                Some(_) => Span::styled("· ", Style::default().fg(theme.sub)),
+                None => Span::raw("  "),
+            });
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    lines
+}
+
+/// WPM and raw WPM over the test as lines, with the seconds that had mistakes marked on
+/// the raw line.
+fn timeline_chart<B: Backend>(f: &mut Frame<B>, app: &App, timeline: &[Sample], area: Rect) {
+    let theme = &app.theme;
+    let wpm = timeline.iter().map(|s| (s.second, s.wpm)).collect::<Vec<_>>();
+    let raw = timeline.iter().map(|s| (s.second, s.raw)).collect::<Vec<_>>();
+    let errors = timeline
+        .iter()
+        .filter(|s| s.errors > 0)
+        .map(|s| (s.second, s.raw))
+        .collect::<Vec<_>>();
+    let seconds = timeline.last().map_or(1.0, |s| s.second).max(1.0);
+    // The WPM axis ends on the next multiple of 20 above the fastest sample
+    let fastest = timeline.iter().map(|s| s.wpm.max(s.raw)).fold(0.0, f64::max);
+    let top = ((fastest / 20.0).ceil() * 20.0).max(20.0);
+
+    let datasets = vec![
+        Dataset::default()
+            .name("raw")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.sub))
+            .data(&raw),
+        Dataset::default()
+            .name("wpm")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.accent))
+            .data(&wpm),
+        Dataset::default()
+            .name("errors")
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(theme.incorrect))
+            .data(&errors),
+    ];
+    let label = |text: String| Span::styled(text, Style::default().fg(theme.sub));
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .title(label("seconds".to_string()))
+                .bounds([0.0, seconds])
+                .labels(vec![label("0".to_string()), label(format!("{:.0}", seconds))]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, top])
+                .labels(vec![label("0".to_string()), label(format!("{:.0}", top))]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Columns between words, and between the letters of a word with `large_text`.
+const WORD_GAP: usize = 1;
+const LARGE_WORD_GAP: usize = 3;
+const LARGE_LETTER_GAP: usize = 1;
+
+/// Columns taken by `word`, wide CJK characters take two.
+pub fn word_width(word: &str, large: bool) -> usize {
+    match large {
+        false => word.width(),
+        true => word.width() + word.graphemes(true).count().saturating_sub(1) * LARGE_LETTER_GAP,
+    }
+}
+
+/// `text` with its graphemes spaced apart for `large_text`.
+fn spaced(text: &str) -> String {
+    text.graphemes(true).collect::<Vec<_>>().join(&" ".repeat(LARGE_LETTER_GAP))
+}
+
+/// Splits words into lines at most `width` columns wide, returning each line's first word index.
+/// Widths are terminal columns, so wide CJK characters take two.
+pub fn line_starts(words: &[String], width: usize, large: bool) -> Vec<usize> {
+    let gap = if large { LARGE_WORD_GAP } else { WORD_GAP };
+    let mut starts = vec![0];
+    let mut used = 0;
+
+    for (i, word) in words.iter().enumerate() {
+        let len = word_width(word, large) + gap;
+        if used > 0 && used + len > width {
+            starts.push(i);
+            used = 0;
+        }
+        used += len;
+    }
+
+    starts
+}
+
+/// The lines of words to show in `width` columns and `height` rows, with the row and column
+/// of the terminal cursor when it is used as the caret.
+fn word_lines(
+    app: &App,
+    width: usize,
+    height: usize,
+) -> (Vec<Spans<'static>>, Option<(usize, usize)>) {
+    if app.config.tape_mode {
+        return tape_lines(app, width, height);
+    }
+    let config = &app.config;
+    let engine = &app.engine;
+    let current = engine.typed.len();
+
+    // Large text leaves an empty line under each line of words
+    let large = config.large_text;
+    let height = if large { height.div_ceil(2) } else { height };
+
+    let starts = line_starts(&engine.words, width.max(1), large);
+    let current_line = starts.iter().rposition(|s| *s <= current).unwrap_or(0);
+    let (first_line, padding) = scroll(current_line, height, config.reduce_motion);
+
+    let mut lines = vec![Spans::default(); padding * if large { 2 } else { 1 }];
+    let mut cursor = None;
+    let shown = height.max(1).saturating_sub(padding).max(1);
+    for (line, start) in starts.iter().enumerate().skip(first_line).take(shown) {
+        let end = starts.get(line + 1).copied().unwrap_or(engine.words.len());
+        let mut spans = Vec::new();
+        let mut caret = None;
+        for i in *start..end {
+            let (word, at) = word_spans(app, i);
+            caret = caret.or(at.map(|at| spans.len() + at));
+            spans.extend(word);
+        }
+
+        let (line, column) = caret_line(app, spans, caret, width);
+        cursor = cursor.or(column.map(|column| (lines.len(), column)));
+        lines.push(line);
+        if large {
+            lines.push(Spans::default());
+        }
+    }
+
+    (lines, cursor)
+}
+
+/// The words on a single line halfway down `height` rows, scrolled so the caret stays in
+/// the middle of the `width` columns.
+fn tape_lines(
+    app: &App,
+    width: usize,
+    height: usize,
+) -> (Vec<Spans<'static>>, Option<(usize, usize)>) {
+    let engine = &app.engine;
+    let current = engine.typed.len();
+    let large = app.config.large_text;
+    let gap = if large { LARGE_WORD_GAP } else { WORD_GAP };
+
+    // Only the words a width either side of the caret can scroll into sight
+    let mut first = current.min(engine.words.len());
+    let mut before = 0;
+    while first > 0 && before < width {
+        first -= 1;
+        before += word_width(&engine.words[first], large) + gap;
+    }
+
+    let mut spans = Vec::<Span>::new();
+    let mut caret = None;
+    let mut caret_column = 0;
+    let mut column = 0;
+    for i in first..engine.words.len() {
+        if caret.is_some() && column > caret_column + width {
+            break;
+        }
+        let (word, at) = word_spans(app, i);
+        if let Some(at) = at {
+            caret = Some(spans.len() + at);
+            caret_column = column + word[..at].iter().map(Span::width).sum::<usize>();
+        }
+        column += word.iter().map(Span::width).sum::<usize>();
+        spans.extend(word);
+    }
+
+    let (skip, padding) = tape_offset(caret_column, width);
+    let (mut spans, mut caret) = tape_window(spans, caret, skip, width - padding.min(width));
+    if padding > 0 {
+        spans.insert(0, Span::raw(" ".repeat(padding)));
+        caret = caret.map(|c| c + 1);
+    }
+
+    let row = height.saturating_sub(1) / 2;
+    let (line, column) = caret_line(app, spans, caret, width);
+    let mut lines = vec![Spans::default(); row];
+    lines.push(line);
+    (lines, column.map(|column| (row, column)))
+}
+
+/// The spans of word `i` and the gap after it, styled by how it was typed, along with the
+/// index of the span the caret is on if it is the word being typed.
+fn word_spans(app: &App, i: usize) -> (Vec<Span<'static>>, Option<usize>) {
+    let theme = &app.theme;
+    let config = &app.config;
+    let engine = &app.engine;
+    let current = engine.typed.len();
+    let large = config.large_text;
+    let gap = if large { LARGE_WORD_GAP } else { WORD_GAP };
+
+    // Mistakes are also told apart by their look when text cues are on
+    let cue = |modifier: Modifier| match config.text_cues {
+        true => modifier,
+        false => Modifier::empty(),
+    };
+    let incorrect = Style::default()
+        .fg(theme.incorrect)
+        .add_modifier(cue(Modifier::BOLD | Modifier::UNDERLINED));
+    let extra = Style::default().fg(theme.extra).add_modifier(cue(Modifier::CROSSED_OUT));
+
+    let word = &engine.words[i];
+    let typed = if i < current {
+        Some(engine.typed[i].as_str())
+    } else if i == current {
+        Some(engine.input.as_str())
+    } else {
+        None
+    };
+
+    let mut word_spans = Vec::new();
+    let mut caret = None;
+    let typed_spans = typed.map_or(0, |typed| typed.graphemes(true).count());
+    match typed {
+        Some(typed) => {
+            // The grapheme still being composed counts as right while it could be
+            let mut expected = word.graphemes(true);
+            for g in typed.graphemes(true) {
+                let style = match expected.next() {
+                    // Blind mode keeps mistakes to the results
+                    _ if config.blind_mode => Style::default().fg(theme.correct),
+                    Some(e) if e == g => Style::default().fg(theme.correct),
+                    Some(e) if i == current && grapheme_matches(e, g) => {
+                        Style::default().fg(theme.correct)
+                    }
+                    Some(_) => incorrect,
+                    None => extra,
+                };
+                word_spans.push(Span::styled(g.to_string(), style));
+            }
+            let mut rest = expected.collect::<Vec<_>>();
+            if i == current && !rest.is_empty() {
+                // The caret is on the next grapheme to type
+                caret = Some(word_spans.len());
+                let next = rest.remove(0).to_string();
+                word_spans.push(Span::styled(next, Style::default().fg(theme.text)));
+            }
+            if !rest.is_empty() {
+                let style = if i < current && !config.blind_mode {
+                    Style::default().fg(theme.incorrect).add_modifier(Modifier::UNDERLINED)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                word_spans.push(Span::styled(rest.concat(), style));
+            }
+        }
+        None => word_spans.push(Span::styled(word.clone(), Style::default().fg(theme.text))),
+    }
+    if config.memory_mode && app.memory.is_hidden(i, Instant::now()) {
+        for span in word_spans.iter_mut().skip(typed_spans) {
+            span.content = masked(&span.content).into();
+            span.style = Style::default().fg(theme.sub);
+        }
+    }
+
+    let mut spans = Vec::new();
+    if large {
+        let letter_gap = " ".repeat(LARGE_LETTER_GAP);
+        for (n, mut span) in word_spans.into_iter().enumerate() {
+            if n > 0 {
+                spans.push(Span::raw(letter_gap.clone()));
+            }
+            if caret == Some(n) {
+                caret = Some(spans.len());
+            }
+            span.content = spaced(&span.content).into();
+            spans.push(span);
         }
+    } else {
+        spans.extend(word_spans);
     }
+
+    // Once the word is typed out the caret waits on the gap for the space
+    if i == current && caret.is_none() {
+        caret = Some(spans.len());
+        spans.push(Span::raw(" "));
+        if gap > 1 {
+            spans.push(Span::raw(" ".repeat(gap - 1)));
+        }
+    } else {
+        spans.push(Span::raw(" ".repeat(gap)));
+    }
+
+    (spans, caret)
+}
+
+/// A line of words from `spans`, mirrored for right to left words and with the caret drawn
+/// on the span `caret` while typing. A line caret is left to the terminal cursor instead,
+/// whose column within `width` columns is returned.
+fn caret_line(
+    app: &App,
+    mut spans: Vec<Span<'static>>,
+    mut caret: Option<usize>,
+    width: usize,
+) -> (Spans<'static>, Option<usize>) {
+    // Mirrored for right to left words, the first word ends up at the right edge
+    let rtl = app.language.direction == LanguageDirection::Rtl;
+    if rtl {
+        spans.reverse();
+        for span in &mut spans {
+            span.content = LanguageDirection::Rtl.visual(&span.content).into();
+        }
+        caret = caret.map(|c| spans.len() - 1 - c);
+    }
+
+    let config = &app.config;
+    let blink = config.caret_blink && !config.reduce_motion;
+    let shown = app.input_mode == InputMode::Typing
+        && caret_shown(app.caret_moved.elapsed(), blink);
+    let caret_style = Style::default().fg(app.theme.caret);
+    let mut cursor = None;
+    match caret {
+        // Reversed, the caret color fills the cell behind the character
+        Some(index) if shown => match config.caret {
+            CaretStyle::Block => {
+                spans[index].style =
+                    spans[index].style.patch(caret_style).add_modifier(Modifier::REVERSED)
+            }
+            CaretStyle::Underline => {
+                spans[index].style =
+                    spans[index].style.patch(caret_style).add_modifier(Modifier::UNDERLINED)
+            }
+            CaretStyle::Line => {
+                // The bar is drawn on the left of a cell, right to left it goes after the caret
+                let before = spans[..index].iter().map(Span::width).sum::<usize>();
+                let column = match rtl {
+                    true => before + spans[index].width(),
+                    false => before,
+                };
+                let offset = match rtl {
+                    true => width.saturating_sub(spans.iter().map(Span::width).sum()),
+                    false => 0,
+                };
+                cursor = Some((offset + column).min(width.saturating_sub(1)));
+            }
+            CaretStyle::Off => {}
+        },
+        _ => {}
+    }
+
+    (Spans::from(spans), cursor)
+}
+
+fn help_overlay<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let theme = &app.theme;
+    let screen = app.screen();
+
+    let mut rows = app
+        .keymap
+        .bindings(screen)
+        .into_iter()
+        .filter(|(_, chords)| !chords.is_empty())
+        .map(|(action, chords)| {
+            let keys = chords.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+            Row::new(vec![
+                Cell::from(keys.join(", ")).style(Style::default().fg(theme.accent)),
+                Cell::from(action.name().replace('_', " ")),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    for conflict in app.keymap.conflicts() {
+        rows.push(Row::new(vec![
+            Cell::from("conflict").style(Style::default().fg(theme.incorrect)),
+            Cell::from(conflict.to_string()),
+        ]));
+    }
+
+    let table = Table::new(rows)
+        .block(
+            Block::default()
+                .title(format!("Key bindings ({})", screen.name()))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(theme.background).fg(theme.text)),
+        )
+        .widths(&[Constraint::Percentage(40), Constraint::Percentage(60)]);
+
+    let area = centered_rect(60, 60, f.size());
+    f.render_widget(Clear, area);
+    f.render_widget(table, area);
 }
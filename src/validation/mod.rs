@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod validation;
+#[allow(clippy::module_inception)]
+mod tests;
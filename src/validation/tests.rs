@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::engine::engine::{KeyKind, Keystroke};
+    use crate::serializers::wpm_results::WpmResult;
+    use crate::validation::validation::*;
+
+    /// Keystrokes typing `text` with `interval` between keys, `<` standing for backspace.
+    fn typing(text: &str, interval: Duration) -> Vec<Keystroke> {
+        text.chars()
+            .enumerate()
+            .map(|(i, c)| Keystroke {
+                at: interval * i as u32,
+                key: match c {
+                    '<' => KeyKind::Backspace,
+                    c => KeyKind::Char(c),
+                },
+                correct: c != 'x',
+                blocked: false,
+            })
+            .collect()
+    }
+
+    /// The result the engine would give for `replay`.
+    fn result(replay: &[Keystroke]) -> WpmResult {
+        let chars = replay.iter().filter(|k| matches!(k.key, KeyKind::Char(_))).count();
+        let minutes = replay.last().unwrap().at.as_secs_f64() / 60.0;
+        WpmResult::new(chars as f64 / 5.0 / minutes, 1.0, minutes, chars as f64 / 5.0 / minutes)
+    }
+
+    #[test]
+    fn test_reconstruct() {
+        let replay = typing("the cax<t sat", Duration::from_millis(150));
+        assert_eq!(reconstruct(&replay), "the cat sat");
+
+        // Backspace removes an accented letter typed as letter and accent in one go
+        let replay = typing("cafe\u{301}<e\u{301}", Duration::from_millis(150));
+        assert_eq!(reconstruct(&replay), "café");
+
+        // Keys held back by stop on error never reached the text
+        let mut replay = typing("cxat", Duration::from_millis(150));
+        replay[1].blocked = true;
+        assert_eq!(reconstruct(&replay), "cat");
+    }
+
+    #[test]
+    fn test_validate() {
+        let human = typing("the cax<t sat on the mat", Duration::from_millis(150));
+        assert_eq!(validate(&result(&human), &human, "the cat sat on the mat"), vec![]);
+        assert_eq!(
+            validate(&result(&human), &human, "the cat sat on the rug"),
+            vec![Flag::ReplayMismatch]
+        );
+
+        let mut inflated = result(&human);
+        inflated.wpm *= 2.0;
+        let flags = validate(&inflated, &human, "the cat sat on the mat");
+        assert_eq!(flags, vec![Flag::ReplayMismatch]);
+
+        let mut pasted = typing("the cat ", Duration::from_millis(150));
+        let at = pasted.last().unwrap().at;
+        for mut keystroke in typing("sat on the mat", Duration::from_micros(500)) {
+            keystroke.at += at;
+            pasted.push(keystroke);
+        }
+        assert_eq!(
+            validate(&result(&pasted), &pasted, "the cat sat on the mat"),
+            vec![Flag::PasteBurst]
+        );
+
+        let robot = typing("the cat sat on the mat", Duration::from_millis(10));
+        let flags = validate(&result(&robot), &robot, "the cat sat on the mat");
+        assert_eq!(flags, vec![Flag::TooFast, Flag::Implausible]);
+    }
+
+    #[test]
+    fn test_integrity() {
+        let replay = typing("the cat", Duration::from_millis(150));
+        let mut result = result(&replay);
+        seal(&mut result, &replay);
+
+        let mut saved: WpmResult = serde_json::from_str(&result.to_json()).unwrap();
+        verify(&mut saved, &replay);
+        assert!(saved.is_valid());
+
+        saved.wpm += 10.0;
+        verify(&mut saved, &replay);
+        assert_eq!(saved.flags, vec![Flag::Tampered]);
+
+        let mut absurd = WpmResult::new(900.0, 1.0, 0.5, 900.0);
+        verify(&mut absurd, &[]);
+        assert_eq!(absurd.flags, vec![Flag::Implausible]);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::calculators::calculators::calculate_wpm;
+use crate::engine::engine::{pop_grapheme, push_char, KeyKind, Keystroke};
+use crate::serializers::wpm_results::WpmResult;
+
+/// Faster than anyone has sustained, results above it are implausible.
+pub const MAX_WPM: f64 = 350.0;
+/// This many characters in a row arriving less than `BURST_INTERVAL` apart is a paste.
+const BURST_KEYS: usize = 8;
+const BURST_INTERVAL: Duration = Duration::from_millis(4);
+/// A mean time between characters below this is beyond human typing, about 600 WPM.
+const MIN_MEAN_INTERVAL: Duration = Duration::from_millis(20);
+/// How far the WPM of a result may be from the WPM its keystrokes add up to.
+const WPM_TOLERANCE: f64 = 0.5;
+
+/// Why a result is not trusted. Flagged results are kept but never count as bests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    /// Many characters arrived at once, as when text is pasted.
+    PasteBurst,
+    /// Keys came in faster than a person can type.
+    TooFast,
+    /// The keystrokes do not add up to the typed text or the saved numbers.
+    ReplayMismatch,
+    /// Numbers out of any possible range.
+    Implausible,
+    /// The result or its keystrokes changed after the test was saved.
+    Tampered,
+}
+
+impl Flag {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Flag::PasteBurst => "pasted text",
+            Flag::TooFast => "impossibly fast keys",
+            Flag::ReplayMismatch => "keystrokes do not match the result",
+            Flag::Implausible => "implausible numbers",
+            Flag::Tampered => "edited after the test",
+        }
+    }
+}
+
+/// The words typed according to `replay`, replayed the way the engine applies keys.
+pub fn reconstruct(replay: &[Keystroke]) -> String {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for keystroke in replay.iter().filter(|k| !k.blocked) {
+        match keystroke.key {
+            KeyKind::Char(' ') => words.push(std::mem::take(&mut current)),
+            KeyKind::Char(c) => push_char(&mut current, c),
+            KeyKind::Backspace => {
+                pop_grapheme(&mut current);
+            }
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words.join(" ")
+}
+
+fn char_intervals(replay: &[Keystroke]) -> Vec<Duration> {
+    let chars = replay.iter().filter(|k| matches!(k.key, KeyKind::Char(_))).collect::<Vec<_>>();
+    chars.windows(2).map(|pair| pair[1].at.saturating_sub(pair[0].at)).collect()
+}
+
+fn implausible(result: &WpmResult) -> bool {
+    !(0.0..=MAX_WPM).contains(&result.wpm)
+        || !(0.0..=1.0).contains(&result.accuracy)
+        || result.duration < 0.0
+        || result.awpm > result.wpm + WPM_TOLERANCE
+}
+
+/// Checks a result that was just typed against its keystrokes and the text `typed`,
+/// returning what looks wrong.
+pub fn validate(result: &WpmResult, replay: &[Keystroke], typed: &str) -> Vec<Flag> {
+    let mut flags = Vec::new();
+    let intervals = char_intervals(replay);
+
+    let burst = intervals
+        .split(|interval| *interval >= BURST_INTERVAL)
+        .any(|run| run.len() + 1 >= BURST_KEYS);
+    if burst {
+        flags.push(Flag::PasteBurst);
+    }
+
+    if !intervals.is_empty() {
+        let mean = intervals.iter().sum::<Duration>() / intervals.len() as u32;
+        if mean < MIN_MEAN_INTERVAL {
+            flags.push(Flag::TooFast);
+        }
+    }
+
+    let chars = replay.iter().filter(|k| matches!(k.key, KeyKind::Char(_))).count();
+    let replay_wpm = match result.duration > 0.0 {
+        true => calculate_wpm(chars as f32, result.duration as f32) as f64,
+        false => 0.0,
+    };
+    if reconstruct(replay) != typed || (replay_wpm - result.wpm).abs() > WPM_TOLERANCE {
+        flags.push(Flag::ReplayMismatch);
+    }
+
+    if implausible(result) {
+        flags.push(Flag::Implausible);
+    }
+    flags
+}
+
+/// FNV-1a, small and stable across builds, unlike the hasher of the standard library.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// A hash over the result, its flags included, and the keystrokes that produced it. The
+/// time is hashed as an instant, so a change of time zone leaves the hash alone, and tags
+/// are left out since they are added afterwards.
+pub fn integrity_hash(result: &WpmResult, replay: &[Keystroke]) -> String {
+    let mut unsealed = result.clone();
+    unsealed.integrity = None;
+    unsealed.tags.clear();
+    let mut value = serde_json::to_value(&unsealed).unwrap_or_default();
+    value["date_time"] = result.date_time.timestamp_micros().into();
+    let json = serde_json::to_string(&(value, replay)).unwrap_or_default();
+    format!("{:016x}", fnv1a(json.as_bytes()))
+}
+
+/// Records the integrity hash, done last once nothing else about the result changes.
+pub fn seal(result: &mut WpmResult, replay: &[Keystroke]) {
+    result.integrity = Some(integrity_hash(result, replay));
+}
+
+/// Flags a saved result whose numbers are out of range, or that no longer matches the
+/// hash recorded when it was saved. Results saved without a hash cannot be checked.
+pub fn verify(result: &mut WpmResult, replay: &[Keystroke]) {
+    let mut flags = Vec::new();
+    if implausible(result) {
+        flags.push(Flag::Implausible);
+    }
+    if let Some(integrity) = &result.integrity {
+        if *integrity != integrity_hash(result, replay) {
+            flags.push(Flag::Tampered);
+        }
+    }
+    for flag in flags {
+        if !result.flags.contains(&flag) {
+            result.flags.push(flag);
+        }
+    }
+}